@@ -0,0 +1,297 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use serde::Deserialize;
+
+/// Everything a key can be bound to.
+/// Keys are resolved to actions by `Keymap`, and components only handle actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Action {
+    Quit,
+    FocusNext,
+    FocusPrev,
+    Submit,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    CursorStart,
+    CursorEnd,
+    CursorForward,
+    CursorBackward,
+    DeleteForward,
+    DeleteBackward,
+    ToggleHelp,
+    CommandPalette,
+    RunQuery,
+    ClearResults,
+    CancelQuery,
+    FocusLogFilter,
+    FocusDuration,
+    FocusGroups,
+    FocusLogs,
+    FocusFind,
+    VisualSelect,
+    Yank,
+    YankJson,
+    ToggleFilter,
+    FindNext,
+    FindPrev,
+    ToggleRegex,
+    ToggleCaseSensitive,
+    ToggleWholeWord,
+    PinHighlight,
+    UnpinHighlight,
+    CycleMinLevel,
+    CycleCollapse,
+    ShowPatterns,
+    CycleTimeZone,
+    TimePicker,
+    ShiftEarlier,
+    ShiftLater,
+    ZoomIn,
+    ZoomOut,
+    AroundSelected,
+    LoadOlder,
+    LoadAll,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PrevTab,
+    ToggleSplit,
+    SwitchPane,
+    ToggleSyncScroll,
+    DiffTabs,
+    CycleDiffSide,
+    RestoreSession,
+    CopyConsoleLink,
+    /// Typed character. Not bindable, used for unbound keys in inputs.
+    Insert(char),
+}
+
+impl Action {
+    /// Bindable actions, in the order they are listed to users.
+    pub const ALL: &'static [Action] = &[
+        Action::Quit,
+        Action::FocusNext,
+        Action::FocusPrev,
+        Action::Submit,
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::CursorStart,
+        Action::CursorEnd,
+        Action::CursorForward,
+        Action::CursorBackward,
+        Action::DeleteForward,
+        Action::DeleteBackward,
+        Action::ToggleHelp,
+        Action::CommandPalette,
+        Action::RunQuery,
+        Action::ClearResults,
+        Action::CancelQuery,
+        Action::FocusLogFilter,
+        Action::FocusDuration,
+        Action::FocusGroups,
+        Action::FocusLogs,
+        Action::FocusFind,
+        Action::VisualSelect,
+        Action::Yank,
+        Action::YankJson,
+        Action::ToggleFilter,
+        Action::FindNext,
+        Action::FindPrev,
+        Action::ToggleRegex,
+        Action::ToggleCaseSensitive,
+        Action::ToggleWholeWord,
+        Action::PinHighlight,
+        Action::UnpinHighlight,
+        Action::CycleMinLevel,
+        Action::CycleCollapse,
+        Action::ShowPatterns,
+        Action::CycleTimeZone,
+        Action::TimePicker,
+        Action::ShiftEarlier,
+        Action::ShiftLater,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::AroundSelected,
+        Action::LoadOlder,
+        Action::LoadAll,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::NextTab,
+        Action::PrevTab,
+        Action::ToggleSplit,
+        Action::SwitchPane,
+        Action::ToggleSyncScroll,
+        Action::DiffTabs,
+        Action::CycleDiffSide,
+        Action::RestoreSession,
+        Action::CopyConsoleLink,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::FocusNext => "focus_next",
+            Action::FocusPrev => "focus_prev",
+            Action::Submit => "submit",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::CursorStart => "cursor_start",
+            Action::CursorEnd => "cursor_end",
+            Action::CursorForward => "cursor_forward",
+            Action::CursorBackward => "cursor_backward",
+            Action::DeleteForward => "delete_forward",
+            Action::DeleteBackward => "delete_backward",
+            Action::ToggleHelp => "help",
+            Action::CommandPalette => "command_palette",
+            Action::RunQuery => "run_query",
+            Action::ClearResults => "clear_results",
+            Action::CancelQuery => "cancel_query",
+            Action::FocusLogFilter => "focus_log_filter",
+            Action::FocusDuration => "focus_duration",
+            Action::FocusGroups => "focus_groups",
+            Action::FocusLogs => "focus_logs",
+            Action::FocusFind => "focus_find",
+            Action::VisualSelect => "visual_select",
+            Action::Yank => "yank",
+            Action::YankJson => "yank_json",
+            Action::ToggleFilter => "toggle_filter",
+            Action::FindNext => "find_next",
+            Action::FindPrev => "find_prev",
+            Action::ToggleRegex => "toggle_regex",
+            Action::ToggleCaseSensitive => "toggle_case_sensitive",
+            Action::ToggleWholeWord => "toggle_whole_word",
+            Action::PinHighlight => "pin_highlight",
+            Action::UnpinHighlight => "unpin_highlight",
+            Action::CycleMinLevel => "cycle_min_level",
+            Action::CycleCollapse => "cycle_collapse",
+            Action::ShowPatterns => "show_patterns",
+            Action::CycleTimeZone => "cycle_time_zone",
+            Action::TimePicker => "time_picker",
+            Action::ShiftEarlier => "shift_earlier",
+            Action::ShiftLater => "shift_later",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::AroundSelected => "around_selected",
+            Action::LoadOlder => "load_older",
+            Action::LoadAll => "load_all",
+            Action::NewTab => "new_tab",
+            Action::CloseTab => "close_tab",
+            Action::NextTab => "next_tab",
+            Action::PrevTab => "prev_tab",
+            Action::ToggleSplit => "toggle_split",
+            Action::SwitchPane => "switch_pane",
+            Action::ToggleSyncScroll => "toggle_sync_scroll",
+            Action::DiffTabs => "diff_tabs",
+            Action::CycleDiffSide => "cycle_diff_side",
+            Action::RestoreSession => "restore_session",
+            Action::CopyConsoleLink => "copy_console_link",
+            Action::Insert(_) => "insert",
+        }
+    }
+
+    /// Short description shown in the help overlay.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::FocusNext => "focus next pane",
+            Action::FocusPrev => "focus previous pane",
+            Action::Submit => "run query",
+            Action::Up => "move up",
+            Action::Down => "move down",
+            Action::PageUp => "page up",
+            Action::PageDown => "page down",
+            Action::Top => "go to top",
+            Action::Bottom => "go to bottom",
+            Action::CursorStart => "move cursor to start",
+            Action::CursorEnd => "move cursor to end",
+            Action::CursorForward => "move cursor forward",
+            Action::CursorBackward => "move cursor backward",
+            Action::DeleteForward => "delete next char",
+            Action::DeleteBackward => "delete previous char",
+            Action::ToggleHelp => "show/hide this help",
+            Action::CommandPalette => "open command palette",
+            Action::RunQuery => "run query",
+            Action::ClearResults => "stop query and clear results",
+            Action::CancelQuery => "stop query and keep the rows found so far",
+            Action::FocusLogFilter => "focus log filter",
+            Action::FocusDuration => "focus duration",
+            Action::FocusGroups => "focus groups",
+            Action::FocusLogs => "focus logs",
+            Action::FocusFind => "focus find string in logs",
+            Action::VisualSelect => "start/stop selecting a range of lines",
+            Action::Yank => "copy selected messages to clipboard",
+            Action::YankJson => "copy selected records to clipboard as JSON",
+            Action::ToggleFilter => "show only lines matching find string",
+            Action::FindNext => "go to next match",
+            Action::FindPrev => "go to previous match",
+            Action::ToggleRegex => "find with regex/literal text",
+            Action::ToggleCaseSensitive => "find case sensitive/insensitive",
+            Action::ToggleWholeWord => "find whole words only/anywhere",
+            Action::PinHighlight => "pin/unpin find string as a highlight",
+            Action::UnpinHighlight => "unpin the last pinned highlight",
+            Action::CycleMinLevel => "hide lines below debug/info/warn/error",
+            Action::CycleCollapse => "collapse consecutive/identical/similar lines",
+            Action::ShowPatterns => "list message patterns of loaded logs",
+            Action::CycleTimeZone => "show times in UTC/local/configured zone/relative",
+            Action::TimePicker => "pick the time range",
+            Action::ShiftEarlier => "query the previous time range",
+            Action::ShiftLater => "query the next time range",
+            Action::ZoomIn => "query the middle half of the time range",
+            Action::ZoomOut => "query twice the time range",
+            Action::AroundSelected => "query the minutes around the selected line",
+            Action::LoadOlder => "load rows older than the loaded ones",
+            Action::LoadAll => "keep loading older rows until the start",
+            Action::NewTab => "open a tab with a copy of the query",
+            Action::CloseTab => "close the tab, stopping its query",
+            Action::NextTab => "show the next tab",
+            Action::PrevTab => "show the previous tab",
+            Action::ToggleSplit => "compare with another tab side by side, stacked, or not",
+            Action::SwitchPane => "move to the other pane of the split",
+            Action::ToggleSyncScroll => "toggle following the selected time in the other pane",
+            Action::DiffTabs => "open a tab diffing the results of two tabs",
+            Action::CycleDiffSide => "show rows only in B, only in A, in both, or all",
+            Action::RestoreSession => "reopen the tabs and results saved on the last exit",
+            Action::CopyConsoleLink => "copy a link opening the query in the AWS console",
+            Action::Insert(_) => "insert char",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .iter()
+            .find(|a| a.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown action {:?}", s))
+    }
+}
+
+impl TryFrom<String> for Action {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
@@ -1,19 +1,27 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::{collections::HashMap, time::Instant};
 
-use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-use tui::{
-    style::{Color, Style},
-    widgets::{Block, Borders},
-};
+use anyhow::Result;
 
-use crossterm::event::KeyModifiers;
+use tui::{layout::Rect, style::Style};
 
+use super::{Action, Split, Tab, TabId};
+use crate::clipboard;
+use crate::config::{Config, KeyContext, Keymap, Resolved, Theme};
 use crate::{client::*, components::*};
-use crate::{models::Duration, option::Opt};
+use crate::{
+    models::{template, ConsoleLink, Duration, LinkRange, TimestampStyle, Zone},
+    option::Opt,
+    session::{self, Row, Session, TabSession},
+};
 pub(crate) const SPECIFIABLE_GROUPS_COUNT: usize = 20;
+const DEFAULT_SPLIT_PERCENT: u16 = 30;
+const MOUSE_SCROLL_LINES: usize = 3;
+/// How often relative times like `3m ago` are shown again.
+const TIME_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FocusTarget {
     LogFilter,
     Duration,
@@ -23,26 +31,183 @@ pub enum FocusTarget {
     FindStringInLogs,
 }
 
+impl FocusTarget {
+    pub fn key_context(&self) -> KeyContext {
+        match self {
+            FocusTarget::LogFilter
+            | FocusTarget::Duration
+            | FocusTarget::GroupFilter
+            | FocusTarget::FindStringInLogs => KeyContext::Input,
+            FocusTarget::Groups => KeyContext::Groups,
+            FocusTarget::Logs => KeyContext::Logs,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            FocusTarget::LogFilter => "Log filter",
+            FocusTarget::Duration => "Duration",
+            FocusTarget::GroupFilter => "Group filter",
+            FocusTarget::Groups => "Groups",
+            FocusTarget::Logs => "Logs",
+            FocusTarget::FindStringInLogs => "Find string in logs",
+        }
+    }
+
+    /// Whether the pane does something with `action`.
+    pub fn handles(&self, action: Action) -> bool {
+        match action {
+            Action::Quit
+            | Action::FocusNext
+            | Action::FocusPrev
+            | Action::ToggleHelp
+            | Action::CommandPalette
+            | Action::RunQuery
+            | Action::ClearResults
+            | Action::CancelQuery
+            | Action::NewTab
+            | Action::CloseTab
+            | Action::NextTab
+            | Action::PrevTab
+            | Action::ToggleSplit
+            | Action::SwitchPane
+            | Action::ToggleSyncScroll
+            | Action::DiffTabs
+            | Action::RestoreSession
+            | Action::CopyConsoleLink
+            | Action::FocusLogFilter
+            | Action::FocusDuration
+            | Action::FocusGroups
+            | Action::FocusLogs
+            | Action::FocusFind
+            | Action::ToggleFilter
+            | Action::FindNext
+            | Action::FindPrev
+            | Action::ToggleRegex
+            | Action::ToggleCaseSensitive
+            | Action::ToggleWholeWord
+            | Action::PinHighlight
+            | Action::UnpinHighlight
+            | Action::CycleMinLevel
+            | Action::CycleCollapse
+            | Action::ShowPatterns
+            | Action::CycleTimeZone => true,
+            Action::TimePicker => *self == FocusTarget::Duration,
+            Action::ShiftEarlier
+            | Action::ShiftLater
+            | Action::ZoomIn
+            | Action::ZoomOut
+            | Action::AroundSelected
+            | Action::LoadOlder
+            | Action::LoadAll
+            | Action::CycleDiffSide => *self == FocusTarget::Logs,
+            Action::VisualSelect | Action::Yank | Action::YankJson => *self == FocusTarget::Logs,
+            Action::Submit => matches!(
+                self,
+                FocusTarget::LogFilter
+                    | FocusTarget::Duration
+                    | FocusTarget::Groups
+                    | FocusTarget::Logs
+            ),
+            Action::Up | Action::Down | Action::Top | Action::Bottom => {
+                matches!(self, FocusTarget::Groups | FocusTarget::Logs)
+            }
+            Action::PageUp | Action::PageDown => *self == FocusTarget::Logs,
+            Action::CursorStart
+            | Action::CursorEnd
+            | Action::CursorForward
+            | Action::CursorBackward
+            | Action::DeleteForward
+            | Action::DeleteBackward
+            | Action::Insert(_) => self.key_context() == KeyContext::Input,
+        }
+    }
+
+    pub fn describe(&self, action: Action) -> &'static str {
+        match (self, action) {
+            (FocusTarget::Groups, Action::Submit) => "toggle the selected group",
+            (FocusTarget::Duration, Action::Submit) => "apply duration and run query",
+            (FocusTarget::Logs, Action::Submit) => "expand/collapse repeated lines",
+            _ => action.description(),
+        }
+    }
+}
+
+/// Areas drawn in the last frame, used to hit test mouse events.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Areas {
+    pub body: Rect,
+    /// Column where the logs pane starts, right of the groups pane.
+    pub divider: u16,
+    pub log_filter: Rect,
+    pub duration: Rect,
+    pub group_filter: Rect,
+    pub groups: Rect,
+    pub logs: Rect,
+    pub find: Rect,
+    /// Legend of pinned highlights in the title of the logs block.
+    pub legend: Rect,
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    area.left() <= column && column < area.right() && area.top() <= row && row < area.bottom()
+}
+
+/// `item` colored by its level.
+fn level_styled(item: LogListItem, theme: &Theme) -> LogListItem {
+    let style = match item.level() {
+        Some(level) => Style::default().fg(theme.level_color(level)),
+        None => Style::default(),
+    };
+    item.style(style)
+}
+
 pub struct App<'a, D>
 where
     D: Dispatcher<Message = Message>,
 {
-    pub loading: bool,
-    pub too_much_groups_specified: bool,
     pub dispatcher: D,
     pub focus_state: FocusTarget,
     pub should_quit: bool,
-    pub should_query_restart: bool,
-    pub group_names: GroupList,
-    pub logs: LogListModel<D>,
-    pub duration: Duration,
-    pub query_started: bool,
-    pub query_completed: bool,
-    pub default_query_input: InputModel<'a>,
-    pub group_filter_input: InputModel<'a>,
-    pub find_string_input: InputModel<'a>,
-    pub duration_input: InputModel<'a>,
-    pub query_id: Option<QueryId>,
+    /// The shown tab.
+    pub tab: Tab<'a, D>,
+    /// The other tabs, in order without the shown one.
+    pub tabs: Vec<Tab<'a, D>>,
+    /// Position of the shown tab among all tabs.
+    pub tab_index: usize,
+    next_tab_id: TabId,
+    /// Tabs compared next to each other, shown while one of them is.
+    pub split: Option<Split>,
+    pub keymap: Keymap,
+    pub pending_keys: Vec<KeyEvent>,
+    pub show_help: bool,
+    pub palette: Option<CommandPalette<'a>>,
+    /// Open pattern list of the loaded logs.
+    pub patterns: Option<PatternList>,
+    /// Open time range picker.
+    pub time_picker: Option<TimePicker>,
+    pub areas: Areas,
+    /// Width of the groups pane in percent of the body.
+    pub split_percent: u16,
+    pub dragging_divider: bool,
+    /// One-off message shown in the status bar until the next key input.
+    pub status_message: Option<String>,
+    pub theme: Theme,
+    /// Configured time zone, one of the zones timestamps can be shown in.
+    pub timezone: Zone,
+    pub around_minutes: i64,
+    /// Max number of rows a query returns.
+    pub limit: usize,
+    /// Region the client resolved, for links to the console.
+    pub region: Option<String>,
+    /// Stored sizes of the log groups, to estimate what a query scans.
+    group_sizes: HashMap<String, GroupSize>,
+    pub price_per_gb: f64,
+    pub cost_warning: f64,
+    query_timeout: Option<std::time::Duration>,
+    times_refreshed_at: Instant,
+    /// Starts of queries in flight, by the tab they were started for.
+    pending_starts: HashMap<TabId, usize>,
 }
 
 pub trait Dispatcher: Sized {
@@ -55,191 +220,1094 @@ pub trait Dispatcher: Sized {
 pub enum Message {
     Tick,
     KeyInput(KeyEvent),
+    MouseInput(MouseEvent),
     GetQueryResultsRequest(QueryId),
-    GetQueryResultsComplete(Vec<SearchResultItem>),
-    StartQueryRequest(StartQueryInput),
-    StartQueryComplete(QueryId),
+    GetQueryResultsRunning(QueryId, Vec<SearchResultItem>, QueryStatistics),
+    GetQueryResultsComplete(QueryId, Vec<SearchResultItem>, QueryStatistics),
+    StartQueryRequest(TabId, StartQueryInput),
+    StartQueryComplete(TabId, QueryId),
     StopQueryRequest(QueryId),
-    UpdateLogListPrevPageStartIndex(usize),
-    UpdateLogListEndIndex(usize),
+    UpdateLogListPrevPageStartIndex(TabId, usize),
+    UpdateLogListEndIndex(TabId, usize),
+    RunAction(Action),
 }
 
 impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
-    pub fn new(dispatcher: D, group_names: Vec<String>, opt: Opt) -> App<'a, D> {
-        let mut default_query_input = InputModel::new()
-            .set_placeholder("Filter your logs")
-            .set_value(opt.filter);
-        default_query_input.focus();
-
-        let group_name_filter = opt.group_name.unwrap_or_default();
-        let group_filter_input = InputModel::new()
-            .set_placeholder("Filter log groups")
-            .set_value(group_name_filter.clone())
-            .block(
-                Block::default()
-                    .borders(Borders::BOTTOM)
-                    .border_style(Style::default()),
-            );
+    pub fn new(
+        dispatcher: D,
+        groups: GetGroupsOutput,
+        opt: Opt,
+        config: Config,
+        region: Option<String>,
+    ) -> App<'a, D> {
+        let GetGroupsOutput {
+            items: group_names,
+            sizes: group_sizes,
+        } = groups;
+        let Config {
+            keymap,
+            theme,
+            timezone,
+            time_format,
+            around_minutes,
+            limit,
+            price_per_gb,
+            cost_warning,
+            query_timeout,
+            ..
+        } = config;
+
+        let group_name_filter = opt.group_name.clone().unwrap_or_default();
         let group_names = GroupList::with_items(group_names, group_name_filter, true);
 
-        let find_string_input = InputModel::new()
-            .set_placeholder("Find string in logs")
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::DarkGray)),
-            )
-            .focused_block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::White)),
-            );
-
-        let duration = Duration::from_opt(&opt.since, opt.end.as_deref());
-        let duration_input_value = if opt.end.is_none() {
-            opt.since
-        } else {
-            format!("{} - {}", opt.since, opt.end.unwrap())
+        let duration = Duration::from_opt(opt.since(), opt.end.as_deref(), &timezone);
+        let duration_input_value = match opt.end {
+            Some(ref end) => format!("{} - {}", opt.since(), end),
+            None => opt.since().to_owned(),
         };
 
-        let duration_input = InputModel::new()
-            .set_placeholder("duration(default 15m)")
-            .set_value(duration_input_value)
-            .block(
-                Block::default()
-                    .borders(Borders::LEFT)
-                    .border_style(Style::default().fg(Color::DarkGray)),
-            )
-            .focused_block(
-                Block::default()
-                    .borders(Borders::LEFT)
-                    .border_style(Style::default()),
-            );
+        let mut logs = LogListModel::new(dispatcher.clone());
+        logs.set_timestamp_style(TimestampStyle::new(timezone, time_format));
+        let mut tab = Tab::new(
+            0,
+            logs,
+            group_names,
+            duration,
+            opt.filter.clone(),
+            duration_input_value,
+            &theme,
+        );
+        tab.default_query_input.focus();
 
         App {
-            loading: false,
-            too_much_groups_specified: false,
             focus_state: FocusTarget::LogFilter,
             should_quit: false,
-            should_query_restart: false,
-            group_names,
-            logs: LogListModel::new(dispatcher.clone()),
+            tab,
+            tabs: vec![],
+            tab_index: 0,
+            next_tab_id: 1,
+            split: None,
             dispatcher,
-            duration,
-            query_id: None,
-            query_started: false,
-            query_completed: false,
-            default_query_input,
-            duration_input,
-            group_filter_input,
-            find_string_input,
+            keymap,
+            pending_keys: vec![],
+            show_help: false,
+            palette: None,
+            patterns: None,
+            time_picker: None,
+            areas: Areas::default(),
+            split_percent: DEFAULT_SPLIT_PERCENT,
+            dragging_divider: false,
+            status_message: None,
+            theme,
+            timezone,
+            around_minutes,
+            limit: limit as usize,
+            region,
+            group_sizes,
+            price_per_gb,
+            cost_warning,
+            query_timeout: query_timeout.map(std::time::Duration::from_secs),
+            times_refreshed_at: Instant::now(),
+            pending_starts: HashMap::new(),
         }
     }
 
     pub fn on_key(&mut self, k: KeyEvent) -> Result<()> {
-        match k {
-            KeyEvent {
-                code: KeyCode::Tab,
-                modifiers: KeyModifiers::NONE,
-            } => self.focus_next(),
-            KeyEvent {
-                code: KeyCode::Tab,
-                modifiers: KeyModifiers::CONTROL,
-            } => self.focus_prev(),
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-            } => match self.focus_state {
-                FocusTarget::LogFilter => {
-                    self.should_query_restart = true;
-                    self.request_stop_query();
-                    self.logs.clear();
+        let context = if self.palette.is_some() {
+            KeyContext::Palette
+        } else if self.patterns.is_some() {
+            KeyContext::Patterns
+        } else if self.time_picker.is_some() {
+            KeyContext::Picker
+        } else {
+            self.focus_state.key_context()
+        };
+        self.pending_keys.push(k);
+        let mut resolved = self.keymap.resolve(context, &self.pending_keys);
+        // A pending sequence that did not continue is dropped, and the last key
+        // is tried on its own.
+        if resolved == Resolved::Unbound && self.pending_keys.len() > 1 {
+            self.pending_keys = vec![k];
+            resolved = self.keymap.resolve(context, &self.pending_keys);
+        }
+        match resolved {
+            Resolved::Pending => return Ok(()),
+            Resolved::Action(action) => self.on_action(action),
+            Resolved::Unbound => {
+                if let KeyCode::Char(c) = k.code {
+                    let is_input = context.chain().contains(&KeyContext::Input);
+                    if is_input && (k.modifiers - KeyModifiers::SHIFT).is_empty() {
+                        self.on_action(Action::Insert(c));
+                    }
+                }
+            }
+        }
+        self.pending_keys.clear();
+        self.sync_split();
+        Ok(())
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        if self.show_help {
+            if let Action::Quit | Action::ToggleHelp = action {
+                self.show_help = false;
+            }
+            return;
+        }
+        if let Some(ref mut palette) = self.palette {
+            match action {
+                Action::Quit => self.palette = None,
+                Action::Submit => {
+                    if let Some(action) = palette.selected() {
+                        self.dispatcher.dispatch(Message::RunAction(action));
+                    }
+                    self.palette = None;
+                }
+                _ => palette.on_action(action),
+            }
+            return;
+        }
+        if let Some(ref mut patterns) = self.patterns {
+            match action {
+                Action::Quit => self.patterns = None,
+                Action::Submit => {
+                    if let Some(cluster) = patterns.selected() {
+                        let template = cluster.template.clone();
+                        self.tab.logs.set_pattern(Some(template));
+                        self.focus(FocusTarget::Logs);
+                    }
+                    self.patterns = None;
+                }
+                _ => patterns.on_action(action),
+            }
+            return;
+        }
+        if let Some(ref mut picker) = self.time_picker {
+            match action {
+                Action::Quit => self.time_picker = None,
+                Action::Submit => match picker.range() {
+                    Ok(_) => {
+                        let expression = picker.expression();
+                        let zone = picker.zone;
+                        self.time_picker = None;
+                        self.tab.duration_input.replace(expression);
+                        self.tab.duration = Duration::parse(self.tab.duration_input.value(), &zone);
+                        self.restart_query();
+                    }
+                    Err(e) => self.status_message = Some(e.to_owned()),
+                },
+                _ => picker.on_action(action),
+            }
+            return;
+        }
+        self.status_message = None;
+        match action {
+            Action::Quit
+                if self.focus_state == FocusTarget::Logs && self.tab.logs.is_selecting() =>
+            {
+                self.tab.logs.cancel_selecting();
+            }
+            Action::Quit
+                if self.focus_state == FocusTarget::Logs && self.tab.logs.pattern().is_some() =>
+            {
+                self.tab.logs.set_pattern(None);
+            }
+            Action::Quit => self.should_quit = true,
+            Action::ToggleHelp => self.show_help = true,
+            Action::CommandPalette => self.open_palette(),
+            Action::FocusNext => self.focus_next(),
+            Action::FocusPrev => self.focus_prev(),
+            Action::FocusLogFilter => self.focus(FocusTarget::LogFilter),
+            Action::FocusDuration => self.focus(FocusTarget::Duration),
+            Action::FocusGroups => self.focus(FocusTarget::Groups),
+            Action::FocusLogs => self.focus(FocusTarget::Logs),
+            Action::FocusFind => self.focus(FocusTarget::FindStringInLogs),
+            Action::RunQuery => self.restart_query(),
+            Action::ToggleFilter => self.tab.logs.toggle_filtering(),
+            Action::FindNext | Action::FindPrev => {
+                let found = self.tab.logs.find_next(action == Action::FindNext);
+                self.status_message = Some(match found {
+                    Some((n, count)) => format!("match {}/{}", n, count),
+                    None => "no matches".to_owned(),
+                });
+            }
+            Action::ToggleRegex | Action::ToggleCaseSensitive | Action::ToggleWholeWord => {
+                let mut mode = self.tab.logs.find_mode();
+                match action {
+                    Action::ToggleRegex => mode.regex = !mode.regex,
+                    Action::ToggleCaseSensitive => mode.case_sensitive = !mode.case_sensitive,
+                    _ => mode.whole_word = !mode.whole_word,
+                }
+                self.tab.logs.set_find_mode(mode);
+            }
+            Action::PinHighlight => {
+                if !self.tab.logs.toggle_pin() {
+                    self.status_message = Some("nothing to pin".to_owned());
+                }
+            }
+            Action::UnpinHighlight => {
+                let last = self.tab.logs.pinned().len().saturating_sub(1);
+                self.tab.logs.unpin(last);
+            }
+            Action::CycleMinLevel => {
+                self.tab.logs.cycle_min_level();
+                self.status_message = Some(match self.tab.logs.min_level() {
+                    Some(level) => format!("showing {} and above", level),
+                    None => "showing all levels".to_owned(),
+                });
+            }
+            Action::CycleCollapse => {
+                self.tab.logs.cycle_collapse();
+                self.status_message = Some(format!("collapse {}", self.tab.logs.collapse()));
+            }
+            Action::ShowPatterns => {
+                let messages = self.tab.logs.items.iter().map(|item| item.message());
+                let clusters = template::cluster(messages);
+                if clusters.is_empty() {
+                    self.status_message = Some("no logs to find patterns in".to_owned());
+                } else {
+                    self.patterns = Some(PatternList::new(clusters));
+                }
+            }
+            Action::ShiftEarlier => self.move_duration(self.tab.duration.shift(-1)),
+            Action::ShiftLater => self.move_duration(self.tab.duration.shift(1)),
+            Action::ZoomIn => self.move_duration(self.tab.duration.zoom(0.5)),
+            Action::ZoomOut => self.move_duration(self.tab.duration.zoom(2.0)),
+            Action::AroundSelected => {
+                let half = self.around_minutes * 60;
+                let duration = self
+                    .tab
+                    .logs
+                    .selected_time()
+                    .map(|t| Duration::around(t.timestamp(), half));
+                self.move_duration(duration);
+            }
+            Action::LoadOlder => {
+                self.tab.loading_all = false;
+                self.load_older();
+            }
+            Action::LoadAll => {
+                self.tab.loading_all = self.load_older();
+            }
+            Action::NewTab => self.new_tab(),
+            Action::CloseTab => self.close_tab(),
+            Action::NextTab => self.switch_tab((self.tab_index + 1) % (self.tabs.len() + 1)),
+            Action::PrevTab => {
+                let count = self.tabs.len() + 1;
+                self.switch_tab((self.tab_index + count - 1) % count)
+            }
+            Action::ToggleSplit => self.toggle_split(),
+            Action::DiffTabs => self.diff_tabs(),
+            Action::RestoreSession => {
+                match session::default_session_path().and_then(|path| Session::load(&path)) {
+                    Ok(session) => self.restore(session),
+                    Err(e) => self.status_message = Some(format!("{}", e)),
+                }
+            }
+            Action::CycleDiffSide => {
+                if self.tab.logs.is_diff() {
+                    self.tab.logs.cycle_side();
+                } else {
+                    self.status_message = Some("not a diff. open one with alt-m".to_owned());
+                }
+            }
+            Action::SwitchPane => match self.shown_split() {
+                Some((other, _)) => {
+                    if let Some(i) = self.tabs.iter().position(|t| t.id == other) {
+                        self.switch_tab(if i < self.tab_index { i } else { i + 1 });
+                    }
+                }
+                None => self.status_message = Some("not split".to_owned()),
+            },
+            Action::ToggleSyncScroll => match self.split {
+                Some(ref mut split) => {
+                    split.sync = !split.sync;
+                    self.status_message = Some(if split.sync {
+                        "panes follow the selected time".to_owned()
+                    } else {
+                        "panes scroll on their own".to_owned()
+                    });
+                }
+                None => self.status_message = Some("not split".to_owned()),
+            },
+            Action::TimePicker if self.focus_state == FocusTarget::Duration => {
+                let zone = self.tab.logs.timestamp_style().zone;
+                self.time_picker = Some(TimePicker::new(self.tab.duration_input.value(), zone));
+            }
+            Action::TimePicker => {}
+            Action::CycleTimeZone => {
+                let style = self.tab.logs.timestamp_style().next(self.timezone);
+                self.status_message = Some(if style.relative {
+                    "relative times".to_owned()
+                } else {
+                    format!("times in {}", style.zone)
+                });
+                self.tab.logs.set_timestamp_style(style);
+            }
+            Action::CopyConsoleLink => self.copy_console_link(),
+            Action::Yank => self.yank(false),
+            Action::YankJson => self.yank(true),
+            Action::ClearResults => {
+                self.request_stop_query();
+                self.tab.loading = false;
+                self.tab.logs.clear();
+            }
+            Action::CancelQuery => {
+                if self.cancel_query() {
+                    self.status_message = Some(format!(
+                        "query stopped. {} items kept.",
+                        self.tab.logs.items.len()
+                    ));
+                } else {
+                    self.status_message = Some("no query running".to_owned());
                 }
+            }
+            Action::Submit => match self.focus_state {
+                FocusTarget::LogFilter => self.restart_query(),
                 FocusTarget::Duration => {
-                    let duration: Duration = self.duration_input.value().into();
+                    let zone = self.tab.logs.timestamp_style().zone;
+                    let duration = Duration::parse(self.tab.duration_input.value(), &zone);
                     if duration.is_valid() {
                         // TODO: error handling
-                        self.duration = duration;
-                        self.should_query_restart = true;
-                        self.request_stop_query();
-                        self.logs.clear();
+                        self.tab.duration = duration;
+                        self.restart_query();
                     }
                 }
                 FocusTarget::Groups => {
-                    self.group_names.on_key(k);
-                    self.should_query_restart = true;
-                    self.request_stop_query();
-                    self.logs.clear();
+                    self.tab.group_names.toggle_selected();
+                    self.restart_query();
+                }
+                FocusTarget::Logs => {
+                    if !self.tab.logs.toggle_expanded() {
+                        self.status_message = Some("not a repeated line".to_owned());
+                    }
                 }
                 _ => {}
             },
             _ => match self.focus_state {
-                FocusTarget::LogFilter => self.default_query_input.on_key(k),
-                FocusTarget::Duration => self.duration_input.on_key(k),
+                FocusTarget::LogFilter => self.tab.default_query_input.on_action(action),
+                FocusTarget::Duration => self.tab.duration_input.on_action(action),
                 FocusTarget::GroupFilter => {
-                    self.group_filter_input.on_key(k);
-                    self.group_names.set_filter(self.group_filter_input.value());
+                    self.tab.group_filter_input.on_action(action);
+                    self.tab
+                        .group_names
+                        .set_filter(self.tab.group_filter_input.value());
                 }
-                FocusTarget::Logs => self.logs.on_key(k),
-                FocusTarget::Groups => self.group_names.on_key(k),
+                FocusTarget::Logs => self.tab.logs.on_action(action),
+                FocusTarget::Groups => self.tab.group_names.on_action(action),
                 FocusTarget::FindStringInLogs => {
-                    self.find_string_input.on_key(k);
-                    self.logs.set_find_text(self.find_string_input.value());
+                    self.tab.find_string_input.on_action(action);
+                    self.tab
+                        .logs
+                        .set_find_text(self.tab.find_string_input.value());
                 } // _ => {}
             },
         }
-        Ok(())
+    }
+
+    pub fn on_mouse(&mut self, m: MouseEvent) {
+        if self.show_help
+            || self.palette.is_some()
+            || self.patterns.is_some()
+            || self.time_picker.is_some()
+        {
+            return;
+        }
+        let (column, row) = (m.column, m.row);
+        match m.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let divider = self.areas.divider;
+                // The divider is made of the two adjacent pane borders.
+                if contains(self.areas.body, column, row)
+                    && (column + 1 == divider || column == divider)
+                {
+                    self.dragging_divider = true;
+                    return;
+                }
+                self.on_click(column, row);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging_divider => {
+                let body = self.areas.body;
+                if body.width > 0 {
+                    let percent =
+                        (column.saturating_sub(body.x) as u32 * 100 / body.width as u32) as u16;
+                    self.split_percent = percent.max(10).min(90);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragging_divider = false,
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = m.kind == MouseEventKind::ScrollDown;
+                if contains(self.areas.logs, column, row) {
+                    for _ in 0..MOUSE_SCROLL_LINES {
+                        if down {
+                            self.tab.logs.next_if_exist();
+                        } else {
+                            self.tab.logs.previous_if_exist();
+                        }
+                    }
+                } else if contains(self.areas.groups, column, row) {
+                    if down {
+                        self.tab.group_names.next();
+                    } else {
+                        self.tab.group_names.previous();
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.sync_split();
+    }
+
+    fn on_click(&mut self, column: u16, row: u16) {
+        let areas = self.areas;
+        if contains(areas.legend, column, row) {
+            if let Some(i) = self.tab.logs.pinned_at(column - areas.legend.x) {
+                self.tab.logs.unpin(i);
+            }
+        } else if contains(areas.log_filter, column, row) {
+            self.focus(FocusTarget::LogFilter);
+        } else if contains(areas.duration, column, row) {
+            self.focus(FocusTarget::Duration);
+        } else if contains(areas.group_filter, column, row) {
+            self.focus(FocusTarget::GroupFilter);
+        } else if contains(areas.find, column, row) {
+            self.focus(FocusTarget::FindStringInLogs);
+        } else if contains(areas.groups, column, row) {
+            self.focus(FocusTarget::Groups);
+            if let Some(index) = self
+                .tab
+                .group_names
+                .index_at((row - areas.groups.y) as usize)
+            {
+                self.tab.group_names.state.select(Some(index));
+                // Rows are drawn as highlight symbol, checkbox, space and name.
+                if column == areas.groups.x + 1 {
+                    self.tab.group_names.toggle_selected();
+                    self.restart_query();
+                }
+            }
+        } else if contains(areas.logs, column, row) {
+            self.focus(FocusTarget::Logs);
+            if let Some(row) = self.tab.logs.row_at(row - areas.logs.y, areas.logs.width) {
+                self.tab.logs.select(row);
+            }
+        }
+    }
+
+    fn yank(&mut self, json: bool) {
+        let lines: Vec<String> = self
+            .tab
+            .logs
+            .selected_items()
+            .iter()
+            .map(|item| {
+                if json {
+                    item.to_json()
+                } else {
+                    item.message().to_owned()
+                }
+            })
+            .collect();
+        if lines.is_empty() {
+            return;
+        }
+        let text = lines.join("\n");
+        let (copied, whole) = match clipboard::copy(&text) {
+            Ok(true) => (format!("copied {} lines", lines.len()), true),
+            Ok(false) => (
+                format!(
+                    "copied first {} KB of {} lines",
+                    clipboard::MAX_COPY_BYTES / 1024,
+                    lines.len()
+                ),
+                false,
+            ),
+            Err(e) => {
+                log::error!("failed to copy to clipboard {:?}", e);
+                (format!("failed to copy {} lines", lines.len()), false)
+            }
+        };
+        self.status_message = Some(if whole {
+            copied
+        } else {
+            match clipboard::write_fallback(&text) {
+                Ok(path) => format!("{}, all saved to {:?}", copied, path),
+                Err(e) => format!("{} (failed to save: {})", copied, e),
+            }
+        });
+        self.tab.logs.cancel_selecting();
+    }
+
+    /// Copy a link opening the query of this tab in Logs Insights.
+    fn copy_console_link(&mut self) {
+        let (start, end) = match self.tab.duration {
+            Duration::Duration {
+                start: Some(start),
+                end: Some(end),
+            } => (start, end),
+            _ => {
+                self.status_message = Some("no time range to link".to_owned());
+                return;
+            }
+        };
+        let input = self.query_input(start, end);
+        let url = ConsoleLink {
+            region: self.region.clone(),
+            query: input.query_string(),
+            groups: input.groups,
+            range: LinkRange::Absolute { start, end },
+        }
+        .url();
+        self.status_message = Some(match clipboard::copy(&url) {
+            Ok(true) => "copied console link".to_string(),
+            copied => {
+                if let Err(e) = copied {
+                    log::error!("failed to copy to clipboard {:?}", e);
+                }
+                match clipboard::write_fallback(&url) {
+                    Ok(path) => format!("failed to copy console link, saved to {:?}", path),
+                    Err(_) => url,
+                }
+            }
+        });
+    }
+
+    fn open_palette(&mut self) {
+        let context = self.focus_state.key_context();
+        let commands = Action::ALL
+            .iter()
+            .filter(|a| **a != Action::CommandPalette && self.focus_state.handles(**a))
+            .map(|a| {
+                let keys: Vec<String> = self
+                    .keymap
+                    .effective_keys(context, *a)
+                    .iter()
+                    .map(|k| k.to_string())
+                    .collect();
+                Command {
+                    action: *a,
+                    label: self.focus_state.describe(*a).to_owned(),
+                    keys: keys.join(", "),
+                }
+            })
+            .collect();
+        self.palette = Some(CommandPalette::new(commands));
+    }
+
+    /// Query `duration` instead, writing it into the duration input.
+    fn move_duration(&mut self, duration: Option<Duration>) {
+        let zone = self.tab.logs.timestamp_style().zone;
+        let expression = duration.as_ref().and_then(|d| d.expression(&zone));
+        match (duration, expression) {
+            (Some(duration), Some(expression)) => {
+                self.tab.duration_input.replace(expression);
+                self.tab.duration = duration;
+                self.restart_query();
+            }
+            _ => self.status_message = Some("no time range to move".to_owned()),
+        }
+    }
+
+    pub fn restart_query(&mut self) {
+        self.tab.label = None;
+        self.tab.should_query_restart = true;
+        self.request_stop_query();
+        self.tab.logs.clear();
+        self.tab.truncated = false;
+        self.tab.loading_all = false;
+        self.tab.older_end = None;
+    }
+
+    /// Query the rows older than the oldest loaded one, appending them.
+    /// Returns whether a query was started.
+    fn load_older(&mut self) -> bool {
+        if self.tab.loading {
+            self.status_message = Some("wait for the query to complete".to_owned());
+            return false;
+        }
+        if !self.tab.truncated {
+            self.status_message = Some("no older rows left out".to_owned());
+            return false;
+        }
+        let Duration::Duration { start, .. } = self.tab.duration;
+        let (start, oldest) = match (start, self.tab.logs.oldest_time()) {
+            (Some(start), Some(oldest)) => (start, oldest),
+            _ => return false,
+        };
+        // The end is inclusive, so the results start with rows of the same second.
+        let end = oldest.timestamp();
+        if end <= start || self.tab.older_end == Some(end) {
+            self.status_message = Some(format!(
+                "more than {} rows at {}, narrow the filter",
+                self.limit,
+                self.tab.logs.timestamp_style().format(&oldest)
+            ));
+            return false;
+        }
+        self.tab.boundary = self.tab.logs.keys_at(end);
+        self.tab.older_end = Some(end);
+        self.start_query(self.query_input(start, end));
+        true
+    }
+
+    fn start_query(&mut self, input: StartQueryInput) {
+        self.tab.loading = true;
+        self.tab.statistics = QueryStatistics::default();
+        self.tab.query_started_at = Some(Instant::now());
+        self.tab.query_finished_at = None;
+        self.tab.partial.clear();
+        self.tab.stop_on_start = false;
+        *self.pending_starts.entry(self.tab.id).or_insert(0) += 1;
+        self.dispatcher
+            .dispatch(Message::StartQueryRequest(self.tab.id, input));
+    }
+
+    /// Warn instead of starting `input` the first time its estimated cost is
+    /// over `cost_warning`. Returns whether it warned.
+    fn warn_cost(&mut self, input: &StartQueryInput) -> bool {
+        let now = chrono::Utc::now().timestamp();
+        let bytes: f64 = input
+            .groups
+            .iter()
+            .filter_map(|g| self.group_sizes.get(g))
+            .map(|size| size.estimate(input.start, input.end, now))
+            .sum();
+        let cost = bytes / BYTES_PER_GB * self.price_per_gb;
+        let confirmed = self
+            .tab
+            .warned_query
+            .as_ref()
+            .map_or(false, |warned| warned.is_rerun_of(input));
+        if cost <= self.cost_warning || confirmed {
+            return false;
+        }
+        self.tab.warned_query = Some(input.clone());
+        self.status_message = Some(format!(
+            "this query may scan {} (~${:.2}). run it again to start it.",
+            format_bytes(bytes),
+            cost
+        ));
+        true
+    }
+
+    fn query_input(&self, start: i64, end: i64) -> StartQueryInput {
+        StartQueryInput {
+            start,
+            end,
+            filter: self.tab.default_query_input.value().to_string(),
+            groups: self.tab.group_names.selected.clone().into_iter().collect(),
+        }
+    }
+
+    /// Add the rows of a query which has ended.
+    fn append_results(&mut self, items: Vec<SearchResultItem>) {
+        let theme = &self.theme;
+        let boundary = std::mem::take(&mut self.tab.boundary);
+        let items = items
+            .into_iter()
+            .filter(|item| !boundary.contains(&(item.timestamp.clone(), item.message.clone())));
+        self.tab.logs.extend(items.map(|item| {
+            level_styled(
+                LogListItem::new(item.timestamp, item.message).fields(item.fields),
+                theme,
+            )
+        }));
+        self.tab.partial.clear();
+        self.tab.query_finished_at = Some(Instant::now());
+        self.tab.query_completed = true;
+        self.tab.loading = false;
+    }
+
+    /// Stop the running query, keeping the rows it found so far.
+    /// Returns whether a query was running.
+    fn cancel_query(&mut self) -> bool {
+        if !self.tab.loading {
+            return false;
+        }
+        if self.tab.query_id.is_none() {
+            self.tab.stop_on_start = true;
+        }
+        self.request_stop_query();
+        self.tab.loading_all = false;
+        let items = std::mem::take(&mut self.tab.partial);
+        self.append_results(items);
+        true
     }
 
     pub fn request_stop_query(&mut self) {
-        if let Some(ref id) = self.query_id {
+        if let Some(ref id) = self.tab.query_id {
             log::trace!("stop query");
             self.dispatcher
                 .dispatch(Message::StopQueryRequest(id.clone()));
-            self.query_id = None;
+            self.tab.query_id = None;
+        }
+    }
+
+    /// Poll the query started for tab `id`, or stop it if it is no longer wanted.
+    fn on_query_started(&mut self, id: TabId, query_id: QueryId) {
+        // Starts complete in order, so one with another start of its tab still
+        // in flight was replaced by a restart.
+        let pending = match self.pending_starts.get_mut(&id) {
+            Some(n) => {
+                *n = n.saturating_sub(1);
+                *n
+            }
+            None => 0,
+        };
+        if pending == 0 {
+            self.pending_starts.remove(&id);
+        }
+        let dispatcher = self.dispatcher.clone();
+        let mut started = false;
+        self.in_tab(id, |app| {
+            if pending > 0 {
+                return;
+            }
+            if app.tab.stop_on_start {
+                app.tab.stop_on_start = false;
+                return;
+            }
+            if let Some(replaced) = app.tab.query_id.replace(query_id.clone()) {
+                app.dispatcher.dispatch(Message::StopQueryRequest(replaced));
+            }
+            app.tab.query_started = true;
+            app.tab.query_completed = false;
+            started = true;
+        });
+        // Stop queries of closed tabs and queries stopped or replaced while starting.
+        if started {
+            dispatcher.dispatch(Message::GetQueryResultsRequest(query_id));
+        } else {
+            dispatcher.dispatch(Message::StopQueryRequest(query_id));
+        }
+    }
+
+    /// Stop the query of the shown tab if it has run for `query_timeout`.
+    fn check_timeout(&mut self) {
+        let timed_out = match (self.query_timeout, self.tab.query_started_at) {
+            (Some(timeout), Some(started)) => self.tab.loading && started.elapsed() > timeout,
+            _ => false,
+        };
+        if timed_out && self.cancel_query() {
+            self.status_message = Some(format!(
+                "query of {:?} timed out. {} items kept.",
+                self.tab.title(),
+                self.tab.logs.items.len()
+            ));
+        }
+    }
+
+    /// Run `f` with tab `id` shown, to handle messages of the queries of other tabs.
+    fn in_tab(&mut self, id: TabId, f: impl FnOnce(&mut Self)) {
+        if self.tab.id == id {
+            f(self);
+        } else if let Some(i) = self.tabs.iter().position(|t| t.id == id) {
+            std::mem::swap(&mut self.tab, &mut self.tabs[i]);
+            f(self);
+            std::mem::swap(&mut self.tab, &mut self.tabs[i]);
+        }
+    }
+
+    fn tab_of_query(&self, query_id: &QueryId) -> Option<TabId> {
+        std::iter::once(&self.tab)
+            .chain(&self.tabs)
+            .find(|t| t.query_id.as_ref() == Some(query_id))
+            .map(|t| t.id)
+    }
+
+    fn new_tab(&mut self) {
+        let tab = self
+            .tab
+            .duplicate(self.next_tab_id, self.dispatcher.clone(), &self.theme);
+        self.next_tab_id += 1;
+        let old = std::mem::replace(&mut self.tab, tab);
+        self.tabs.insert(self.tab_index, old);
+        self.tab_index += 1;
+        self.focus(FocusTarget::LogFilter);
+        self.status_message = Some("new tab. edit the query and press enter to run it.".to_owned());
+    }
+
+    fn close_tab(&mut self) {
+        if self.tabs.is_empty() {
+            self.status_message = Some("the last tab can't be closed".to_owned());
+            return;
+        }
+        self.request_stop_query();
+        if let Some(split) = self.split {
+            if split.other(self.tab.id).is_some() {
+                self.split = None;
+            }
+        }
+        // Show the tab after the closed one, or before it if it was the last.
+        if self.tab_index == self.tabs.len() {
+            self.tab_index -= 1;
         }
+        self.tab = self.tabs.remove(self.tab_index);
+        self.focus(self.focus_state);
+    }
+
+    /// Compare with the tab before this one side by side, then stacked, then stop comparing.
+    fn toggle_split(&mut self) {
+        self.split = match self.split {
+            Some(split) if !split.stacked && split.other(self.tab.id).is_some() => Some(Split {
+                stacked: true,
+                ..split
+            }),
+            Some(split) if split.other(self.tab.id).is_some() => None,
+            _ => {
+                let other = if self.tab_index > 0 {
+                    self.tabs.get(self.tab_index - 1)
+                } else {
+                    self.tabs.first()
+                };
+                match other {
+                    Some(other) if self.tab_index > 0 => Some(Split::new(other.id, self.tab.id)),
+                    Some(other) => Some(Split::new(self.tab.id, other.id)),
+                    None => {
+                        self.status_message =
+                            Some("open another tab with alt-n to compare with".to_owned());
+                        None
+                    }
+                }
+            }
+        };
+        self.sync_split();
+    }
+
+    /// Open a tab with the rows of the split tabs, or of this tab and the one
+    /// before it, marked by whether their template is only in the first
+    /// tab (A), only in the second (B) or in both.
+    fn diff_tabs(&mut self) {
+        let other = match self.shown_split() {
+            Some((other, _)) => self.tabs.iter().position(|t| t.id == other),
+            None if self.tab_index > 0 => Some(self.tab_index - 1),
+            None => None,
+        };
+        let (other, other_first) = match other {
+            Some(i) => (&self.tabs[i], i < self.tab_index),
+            None => {
+                self.status_message = Some("open another tab with alt-n to diff with".to_owned());
+                return;
+            }
+        };
+        if self.tab.loading || other.loading {
+            self.status_message = Some("wait for both queries to complete".to_owned());
+            return;
+        }
+        let (a, b) = if other_first {
+            (other, &self.tab)
+        } else {
+            (&self.tab, other)
+        };
+        let (sides_a, sides_b) = template::diff(
+            a.logs.items.iter().map(|item| item.message()),
+            b.logs.items.iter().map(|item| item.message()),
+        );
+        let mut items: Vec<LogListItem> = a
+            .logs
+            .items
+            .iter()
+            .zip(sides_a)
+            .chain(b.logs.items.iter().zip(sides_b))
+            .map(|(item, side)| item.diffed(side))
+            .collect();
+        // Newest first, like query results.
+        items.sort_by(|x, y| y.time().cmp(&x.time()));
+        let label = format!("{} ⇄ {}", a.title(), b.title());
+
+        let mut tab = self
+            .tab
+            .duplicate(self.next_tab_id, self.dispatcher.clone(), &self.theme);
+        self.next_tab_id += 1;
+        tab.label = Some(label);
+        tab.logs.extend(items);
+        let old = std::mem::replace(&mut self.tab, tab);
+        self.tabs.insert(self.tab_index, old);
+        self.tab_index += 1;
+        self.focus(FocusTarget::Logs);
+    }
+
+    /// Replace the tabs with those of `session`, showing their saved results
+    /// without querying.
+    pub fn restore(&mut self, mut session: Session) {
+        if session.tabs.is_empty() {
+            self.status_message = Some("the session has no tabs".to_owned());
+            return;
+        }
+        for query_id in self.query_ids() {
+            self.dispatcher
+                .dispatch(Message::StopQueryRequest(query_id));
+        }
+        // Groups of the session may be missing from the account queried now.
+        let mut names = self.tab.group_names.items.clone();
+        for group in session.groups() {
+            if !names.contains(&group) {
+                names.push(group);
+            }
+        }
+        // The file may have been edited by hand, or by another version.
+        let mut invalid_filters = 0;
+        for saved in session.tabs.iter_mut() {
+            if regex::Regex::new(&saved.group_filter).is_err() {
+                saved.group_filter.clear();
+                invalid_filters += 1;
+            }
+        }
+        let count = session.tabs.len();
+        let mut tabs: Vec<Tab<'a, D>> = session
+            .tabs
+            .into_iter()
+            .map(|saved| self.restored_tab(saved, &names))
+            .collect();
+        self.split = None;
+        self.tab_index = session.tab_index.min(count - 1);
+        self.tab = tabs.remove(self.tab_index);
+        self.tabs = tabs;
+        self.focus(FocusTarget::Logs);
+        self.status_message = Some(if invalid_filters > 0 {
+            format!(
+                "restored {} tabs. {} invalid group filters were cleared.",
+                count, invalid_filters
+            )
+        } else {
+            format!("restored {} tabs", count)
+        });
+    }
+
+    fn restored_tab(&mut self, saved: TabSession, names: &[String]) -> Tab<'a, D> {
+        let mut group_names = GroupList::with_items(names.to_vec(), saved.group_filter, false);
+        group_names.selected = saved.groups.into_iter().collect();
+        let mut logs = LogListModel::new(self.dispatcher.clone());
+        logs.set_timestamp_style(self.tab.logs.timestamp_style().clone());
+        let duration = Duration::Duration {
+            start: saved.start,
+            end: saved.end,
+        };
+        let mut tab = Tab::new(
+            self.next_tab_id,
+            logs,
+            group_names,
+            duration,
+            saved.filter,
+            saved.duration,
+            &self.theme,
+        );
+        self.next_tab_id += 1;
+        tab.label = saved.label;
+        tab.query_started = true;
+        tab.query_completed = true;
+        tab.truncated = saved.truncated;
+        let theme = &self.theme;
+        tab.logs.extend(saved.rows.into_iter().map(|row| {
+            let item = LogListItem::new(row.timestamp, row.message)
+                .fields(row.fields)
+                .diff_side(row.side);
+            level_styled(item, theme)
+        }));
+        tab.find_string_input.replace(saved.find.clone());
+        tab.logs.set_find_text(saved.find);
+        if let Some(row) = saved.selected {
+            tab.logs.select(row);
+        }
+        tab
+    }
+
+    /// Select the row nearest to the selected time in the other pane.
+    fn sync_split(&mut self) {
+        let other = match self.split {
+            Some(split) if split.sync => split.other(self.tab.id),
+            _ => None,
+        };
+        if let (Some(other), Some(time)) = (other, self.tab.logs.selected_time()) {
+            if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == other) {
+                tab.logs.select_nearest(time);
+            }
+        }
+    }
+
+    /// Show the tab at `index` among all tabs.
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.tab_index || index > self.tabs.len() {
+            return;
+        }
+        let position = if index < self.tab_index {
+            index
+        } else {
+            index - 1
+        };
+        let tab = self.tabs.remove(position);
+        let old = std::mem::replace(&mut self.tab, tab);
+        let position = if self.tab_index < index {
+            self.tab_index
+        } else {
+            self.tab_index - 1
+        };
+        self.tabs.insert(position, old);
+        self.tab_index = index;
+        self.focus(self.focus_state);
     }
 
     pub async fn on_tick(&mut self) {
-        if !self.query_started || self.should_query_restart {
+        let ids: Vec<TabId> = std::iter::once(&self.tab)
+            .chain(&self.tabs)
+            .map(|t| t.id)
+            .collect();
+        for id in ids {
+            self.in_tab(id, Self::check_timeout);
+        }
+        if self.times_refreshed_at.elapsed() >= TIME_REFRESH_INTERVAL {
+            self.times_refreshed_at = Instant::now();
+            for tab in std::iter::once(&mut self.tab).chain(self.tabs.iter_mut()) {
+                tab.logs.refresh_times();
+            }
+        }
+        if !self.tab.query_started || self.tab.should_query_restart {
             log::trace!("restart query");
-            self.should_query_restart = false;
-            self.too_much_groups_specified = false;
+            self.tab.should_query_restart = false;
+            self.tab.too_much_groups_specified = false;
 
-            let groups: Vec<String> = self.group_names.selected.clone().into_iter().collect();
+            let groups: Vec<String> = self.tab.group_names.selected.clone().into_iter().collect();
             if groups.len() > SPECIFIABLE_GROUPS_COUNT {
-                self.too_much_groups_specified = true;
-            } else if !groups.is_empty() && self.duration.is_valid() {
-                let Duration::Duration { start, end } = self.duration;
-                self.query_started = true;
-                self.loading = true;
-                self.dispatcher
-                    .dispatch(Message::StartQueryRequest(StartQueryInput {
-                        start: start.unwrap(),
-                        end: end.unwrap(),
-                        filter: self.default_query_input.value().to_string(),
-                        groups,
-                    }));
+                self.tab.too_much_groups_specified = true;
+            } else if !groups.is_empty() && self.tab.duration.is_valid() {
+                let Duration::Duration { start, end } = self.tab.duration;
+                let input = self.query_input(start.unwrap(), end.unwrap());
+                self.tab.query_started = true;
+                if !self.warn_cost(&input) {
+                    self.start_query(input);
+                }
             }
         }
     }
 
     fn blur_all(&mut self) {
-        self.default_query_input.blur();
-        self.duration_input.blur();
-        self.group_filter_input.blur();
-        self.find_string_input.blur();
+        self.tab.default_query_input.blur();
+        self.tab.duration_input.blur();
+        self.tab.group_filter_input.blur();
+        self.tab.find_string_input.blur();
+    }
+
+    pub fn focus(&mut self, target: FocusTarget) {
+        self.blur_all();
+        match target {
+            FocusTarget::LogFilter => self.tab.default_query_input.focus(),
+            FocusTarget::Duration => self.tab.duration_input.focus(),
+            FocusTarget::GroupFilter => self.tab.group_filter_input.focus(),
+            FocusTarget::FindStringInLogs => self.tab.find_string_input.focus(),
+            FocusTarget::Groups | FocusTarget::Logs => {}
+        }
+        self.focus_state = target;
     }
 
     pub fn focus_next(&mut self) {
         self.blur_all();
         match self.focus_state {
             FocusTarget::LogFilter => {
-                self.duration_input.focus();
+                self.tab.duration_input.focus();
                 self.focus_state = FocusTarget::Duration;
             }
             FocusTarget::Duration => {
-                self.group_filter_input.focus();
+                self.tab.group_filter_input.focus();
                 self.focus_state = FocusTarget::GroupFilter;
             }
             FocusTarget::GroupFilter => {
@@ -247,11 +1315,11 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
             }
             FocusTarget::Groups => self.focus_state = FocusTarget::Logs,
             FocusTarget::Logs => {
-                self.find_string_input.focus();
+                self.tab.find_string_input.focus();
                 self.focus_state = FocusTarget::FindStringInLogs;
             }
             FocusTarget::FindStringInLogs => {
-                self.default_query_input.focus();
+                self.tab.default_query_input.focus();
                 self.focus_state = FocusTarget::LogFilter;
             }
         }
@@ -261,19 +1329,19 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
         self.blur_all();
         match self.focus_state {
             FocusTarget::LogFilter => {
-                self.find_string_input.focus();
+                self.tab.find_string_input.focus();
                 self.focus_state = FocusTarget::FindStringInLogs;
             }
             FocusTarget::Duration => {
-                self.default_query_input.focus();
+                self.tab.default_query_input.focus();
                 self.focus_state = FocusTarget::LogFilter;
             }
             FocusTarget::GroupFilter => {
-                self.duration_input.focus();
+                self.tab.duration_input.focus();
                 self.focus_state = FocusTarget::Duration;
             }
             FocusTarget::Groups => {
-                self.group_filter_input.focus();
+                self.tab.group_filter_input.focus();
                 self.focus_state = FocusTarget::GroupFilter;
             }
             FocusTarget::Logs => {
@@ -288,33 +1356,279 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
     pub async fn update(&mut self, message: Message) {
         log::trace!("update message {:?}", message);
         match message {
-            Message::GetQueryResultsComplete(items) => {
-                for item in items {
-                    self.logs
-                        .push(LogListItem::new(item.timestamp, item.message))
+            Message::GetQueryResultsRunning(query_id, items, statistics) => {
+                // Queries stopped meanwhile belong to no tab, and are not polled any more.
+                if let Some(id) = self.tab_of_query(&query_id) {
+                    self.in_tab(id, |app| {
+                        app.tab.statistics = statistics;
+                        app.tab.partial = items;
+                        app.dispatcher
+                            .dispatch(Message::GetQueryResultsRequest(query_id));
+                    });
+                }
+            }
+            Message::GetQueryResultsComplete(query_id, items, statistics) => {
+                if let Some(id) = self.tab_of_query(&query_id) {
+                    self.in_tab(id, |app| {
+                        app.tab.statistics = statistics;
+                        app.tab.truncated = items.len() >= app.limit;
+                        app.append_results(items);
+                        // Queries still running when they hit `limit` are reported
+                        // complete, and are charged until they are stopped.
+                        if app.tab.truncated {
+                            app.request_stop_query();
+                        }
+                        app.tab.query_id = None;
+                        if app.tab.loading_all {
+                            app.tab.loading_all = app.tab.truncated && app.load_older();
+                        }
+                    });
                 }
-                self.query_completed = true;
-                self.loading = false;
-                self.query_id = None;
             }
-            Message::StartQueryComplete(query_id) => {
+            Message::StartQueryComplete(id, query_id) => {
                 log::trace!("StartQueryComplete");
-                self.query_id = Some(query_id.clone());
-                self.query_started = true;
-                self.dispatcher
-                    .dispatch(Message::GetQueryResultsRequest(query_id));
-                self.query_completed = false;
+                self.on_query_started(id, query_id);
             }
             Message::Tick => {
                 self.on_tick().await;
             }
-            Message::UpdateLogListEndIndex(index) => {
-                self.logs.update_end_index(index);
+            Message::UpdateLogListEndIndex(id, index) => {
+                self.in_tab(id, |app| app.tab.logs.update_end_index(index));
             }
-            Message::UpdateLogListPrevPageStartIndex(index) => {
-                self.logs.update_prev_page_start_index(index);
+            Message::UpdateLogListPrevPageStartIndex(id, index) => {
+                self.in_tab(id, |app| app.tab.logs.update_prev_page_start_index(index));
             }
+            Message::RunAction(action) => self.on_action(action),
             _ => {}
         }
     }
 }
+
+impl<'a, D: Dispatcher<Message = Message>> App<'a, D> {
+    /// The tab shown next to this one and whether this one is left or above.
+    pub fn shown_split(&self) -> Option<(TabId, bool)> {
+        let split = self.split?;
+        split
+            .other(self.tab.id)
+            .map(|other| (other, split.left == self.tab.id))
+    }
+
+    /// Queries running in any tab.
+    pub fn query_ids(&self) -> Vec<QueryId> {
+        std::iter::once(&self.tab)
+            .chain(&self.tabs)
+            .filter_map(|t| t.query_id.clone())
+            .collect()
+    }
+
+    /// Tell that the session saved on the last exit can be restored.
+    pub fn offer_restore(&mut self) {
+        let keys: Vec<String> = self
+            .keymap
+            .effective_keys(KeyContext::Global, Action::RestoreSession)
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        self.status_message = Some(match keys.first() {
+            Some(key) => format!("press {} to restore the last session", key),
+            None => "run restore_session to restore the last session".to_owned(),
+        });
+    }
+
+    /// The tabs with their loaded results, to save on exit.
+    pub fn session(&self) -> Session {
+        let mut tabs: Vec<TabSession> = self.tabs.iter().map(|t| tab_session(t)).collect();
+        tabs.insert(self.tab_index, tab_session(&self.tab));
+        Session {
+            tabs,
+            tab_index: self.tab_index,
+        }
+    }
+
+    /// Number of queries started, also for closed tabs, whose id has not arrived yet.
+    pub fn starting_queries(&self) -> usize {
+        self.pending_starts.values().sum()
+    }
+
+    /// Titles of all tabs, in order.
+    pub fn tab_titles(&self) -> Vec<String> {
+        let mut titles: Vec<String> = self.tabs.iter().map(|t| t.title()).collect();
+        titles.insert(self.tab_index, self.tab.title());
+        titles
+    }
+
+    /// Bindings of the focused pane as `(keys, description)`, in `Action::ALL` order.
+    pub fn help_entries(&self) -> Vec<(String, &'static str)> {
+        let context = self.focus_state.key_context();
+        Action::ALL
+            .iter()
+            .filter(|a| self.focus_state.handles(**a))
+            .filter_map(|a| {
+                let keys = self.keymap.effective_keys(context, *a);
+                if keys.is_empty() {
+                    return None;
+                }
+                let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+                Some((keys.join(", "), self.focus_state.describe(*a)))
+            })
+            .collect()
+    }
+
+    /// Share of the time range the loaded rows cover, in percent.
+    pub fn loaded_percent(&self) -> Option<i64> {
+        let Duration::Duration { start, end } = self.tab.duration;
+        let (start, end) = (start?, end?);
+        let oldest = self.tab.logs.oldest_time()?.timestamp();
+        if end <= start {
+            return None;
+        }
+        Some(((end - oldest) * 100 / (end - start)).max(0).min(100))
+    }
+
+    /// One line hint of the most useful bindings of the focused pane.
+    pub fn key_hint(&self) -> String {
+        let context = self.focus_state.key_context();
+        [
+            Action::ToggleHelp,
+            Action::CommandPalette,
+            Action::Submit,
+            Action::Down,
+            Action::Up,
+            Action::FocusNext,
+            Action::Quit,
+        ]
+        .iter()
+        .filter(|a| self.focus_state.handles(**a))
+        .filter_map(|a| {
+            self.keymap
+                .effective_keys(context, *a)
+                .first()
+                .map(|k| format!("{}: {}", k, a.name().replace('_', " ")))
+        })
+        .collect::<Vec<String>>()
+        .join("  ")
+    }
+}
+
+fn tab_session<D: Dispatcher<Message = Message>>(tab: &Tab<D>) -> TabSession {
+    let Duration::Duration { start, end } = tab.duration;
+    TabSession {
+        label: tab.label.clone(),
+        filter: tab.default_query_input.value().to_owned(),
+        duration: tab.duration_input.value().to_owned(),
+        start,
+        end,
+        group_filter: tab.group_names.filter.clone(),
+        groups: tab.group_names.selected.iter().cloned().collect(),
+        find: tab.find_string_input.value().to_owned(),
+        selected: tab.logs.selected_index(),
+        truncated: tab.truncated,
+        rows: tab
+            .logs
+            .items
+            .iter()
+            .map(|item| Row {
+                timestamp: item.timestamp().to_owned(),
+                message: item.message().to_owned(),
+                fields: item.record().to_vec(),
+                side: item.side(),
+            })
+            .collect(),
+    }
+}
+
+/// `bytes` in the largest unit, like `1.2 GB`.
+pub fn format_bytes(bytes: f64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size as u64, units[unit])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use structopt::StructOpt;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Recorder(Rc<RefCell<Vec<Message>>>);
+
+    impl Dispatcher for Recorder {
+        type Message = Message;
+
+        fn dispatch(&self, message: Message) {
+            self.0.borrow_mut().push(message);
+        }
+    }
+
+    fn app(recorder: &Recorder) -> App<'static, Recorder> {
+        let groups = GetGroupsOutput {
+            items: vec!["group".to_owned()],
+            sizes: HashMap::new(),
+        };
+        let config = Config::default();
+        let opt = Opt::from_iter(&["kanten", "--group-name", "group"]).merge(&config);
+        App::new(recorder.clone(), groups, opt, config, None)
+    }
+
+    fn input() -> StartQueryInput {
+        StartQueryInput {
+            start: 0,
+            end: 60,
+            filter: String::new(),
+            groups: vec!["group".to_owned()],
+        }
+    }
+
+    #[test]
+    fn restart_while_start_is_pending() {
+        let recorder = Recorder::default();
+        let mut app = app(&recorder);
+        app.start_query(input());
+        app.restart_query();
+        app.start_query(input());
+        recorder.0.borrow_mut().clear();
+
+        let (first, second) = (QueryId::new("first"), QueryId::new("second"));
+        app.on_query_started(0, first.clone());
+        app.on_query_started(0, second.clone());
+
+        assert_eq!(app.tab.query_id, Some(second.clone()));
+        let messages = recorder.0.borrow();
+        assert!(matches!(&messages[..], [
+            Message::StopQueryRequest(stopped),
+            Message::GetQueryResultsRequest(polled),
+        ] if *stopped == first && *polled == second));
+    }
+
+    #[test]
+    fn start_of_closed_tab_is_stopped() {
+        let recorder = Recorder::default();
+        let mut app = app(&recorder);
+        app.new_tab();
+        app.start_query(input());
+        app.close_tab();
+        recorder.0.borrow_mut().clear();
+
+        let query = QueryId::new("orphan");
+
+        assert_eq!(app.starting_queries(), 1);
+        app.on_query_started(1, query.clone());
+
+        assert_eq!(app.starting_queries(), 0);
+        assert!(app.query_ids().is_empty());
+        let messages = recorder.0.borrow();
+        assert!(matches!(&messages[..], [Message::StopQueryRequest(stopped)] if *stopped == query));
+    }
+}
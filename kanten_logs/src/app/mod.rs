@@ -1,5 +1,9 @@
+mod action;
 #[allow(clippy::module_inception)]
 mod app;
+mod tab;
 
 pub mod view;
+pub use action::*;
 pub use app::*;
+pub use tab::*;
@@ -0,0 +1,210 @@
+use std::{collections::HashSet, time::Instant};
+
+use tui::{
+    style::Style,
+    widgets::{Block, Borders},
+};
+
+use super::{Dispatcher, Message};
+use crate::{client::*, components::*, config::Theme, models::Duration};
+
+/// Identifies a tab for the life of the app, unlike its position.
+pub type TabId = usize;
+
+/// Query form, results and running query of one tab.
+pub struct Tab<'a, D>
+where
+    D: Dispatcher<Message = Message>,
+{
+    pub id: TabId,
+    /// Title overriding the one made of the query, for tabs not made by a query.
+    pub label: Option<String>,
+    pub loading: bool,
+    pub too_much_groups_specified: bool,
+    pub should_query_restart: bool,
+    pub group_names: GroupList,
+    pub logs: LogListModel<D>,
+    pub duration: Duration,
+    pub query_started: bool,
+    pub query_completed: bool,
+    pub default_query_input: InputModel<'a>,
+    pub group_filter_input: InputModel<'a>,
+    pub find_string_input: InputModel<'a>,
+    pub duration_input: InputModel<'a>,
+    pub query_id: Option<QueryId>,
+    /// Whether the last results hit `limit`, so older rows were left out.
+    pub truncated: bool,
+    /// Keep loading older rows until none are left out.
+    pub loading_all: bool,
+    /// End of the last query loading older rows.
+    pub older_end: Option<i64>,
+    /// Rows already loaded in the second at `older_end`, which the results repeat.
+    pub boundary: HashSet<(String, String)>,
+    /// Query not started because of its estimated cost, which starts when run again.
+    pub warned_query: Option<StartQueryInput>,
+    /// Statistics of the running or last query.
+    pub statistics: QueryStatistics,
+    pub query_started_at: Option<Instant>,
+    pub query_finished_at: Option<Instant>,
+    /// Rows the running query found so far.
+    pub partial: Vec<SearchResultItem>,
+    /// Stop the query being started as soon as its id arrives.
+    pub stop_on_start: bool,
+}
+
+/// Two tabs shown next to each other to compare their results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+    pub left: TabId,
+    pub right: TabId,
+    /// One above the other instead of side by side.
+    pub stacked: bool,
+    /// Select the row nearest to the time selected in the other pane.
+    pub sync: bool,
+}
+
+impl Split {
+    pub fn new(left: TabId, right: TabId) -> Self {
+        Split {
+            left,
+            right,
+            stacked: false,
+            sync: true,
+        }
+    }
+
+    /// The tab shown next to tab `id`, if `id` is in the split.
+    pub fn other(&self, id: TabId) -> Option<TabId> {
+        if id == self.left {
+            Some(self.right)
+        } else if id == self.right {
+            Some(self.left)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, D> Tab<'a, D>
+where
+    D: Dispatcher<Message = Message> + Clone,
+{
+    pub fn new(
+        id: TabId,
+        mut logs: LogListModel<D>,
+        group_names: GroupList,
+        duration: Duration,
+        filter: impl Into<String>,
+        duration_value: impl Into<String>,
+        theme: &Theme,
+    ) -> Self {
+        logs.set_tab(id);
+        let default_query_input = InputModel::new()
+            .set_placeholder("Filter your logs")
+            .set_value(filter.into());
+
+        let group_filter_input = InputModel::new()
+            .set_placeholder("Filter log groups")
+            .set_value(group_names.filter.clone())
+            .block(
+                Block::default()
+                    .borders(Borders::BOTTOM)
+                    .border_style(Style::default()),
+            );
+
+        let find_string_input = InputModel::new()
+            .set_placeholder("Find string in logs")
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border)),
+            )
+            .focused_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.focused_border)),
+            );
+
+        let duration_input = InputModel::new()
+            .set_placeholder("duration(default 15m)")
+            .set_value(duration_value.into())
+            .block(
+                Block::default()
+                    .borders(Borders::LEFT)
+                    .border_style(Style::default().fg(theme.border)),
+            )
+            .focused_block(
+                Block::default()
+                    .borders(Borders::LEFT)
+                    .border_style(Style::default()),
+            );
+
+        Tab {
+            id,
+            label: None,
+            loading: false,
+            too_much_groups_specified: false,
+            should_query_restart: false,
+            group_names,
+            logs,
+            duration,
+            query_started: false,
+            query_completed: false,
+            default_query_input,
+            group_filter_input,
+            find_string_input,
+            duration_input,
+            query_id: None,
+            truncated: false,
+            loading_all: false,
+            older_end: None,
+            boundary: HashSet::new(),
+            warned_query: None,
+            statistics: QueryStatistics::default(),
+            query_started_at: None,
+            query_finished_at: None,
+            partial: vec![],
+            stop_on_start: false,
+        }
+    }
+
+    /// New tab `id` with the same query form, not run yet.
+    pub fn duplicate(&self, id: TabId, dispatcher: D, theme: &Theme) -> Self {
+        let mut logs = LogListModel::new(dispatcher);
+        logs.set_timestamp_style(self.logs.timestamp_style().clone());
+        let mut tab = Tab::new(
+            id,
+            logs,
+            self.group_names.clone(),
+            self.duration.clone(),
+            self.default_query_input.value(),
+            self.duration_input.value(),
+            theme,
+        );
+        tab.query_started = true;
+        tab
+    }
+}
+
+impl<'a, D> Tab<'a, D>
+where
+    D: Dispatcher<Message = Message>,
+{
+    /// Label in the tab bar: the filter, or the first group if there is no filter.
+    pub fn title(&self) -> String {
+        if let Some(ref label) = self.label {
+            return label.clone();
+        }
+        let filter = self.default_query_input.value();
+        if !filter.is_empty() {
+            return filter.to_owned();
+        }
+        match self.group_names.selected.iter().next() {
+            Some(group) if self.group_names.selected.len() > 1 => {
+                format!("{} +{}", group, self.group_names.selected.len() - 1)
+            }
+            Some(group) => group.clone(),
+            None => "new query".to_owned(),
+        }
+    }
+}
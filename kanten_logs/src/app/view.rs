@@ -1,31 +1,84 @@
-use crate::app::{app::FocusTarget, App, Dispatcher, Message};
+use std::time::Instant;
+
+use crate::app::{
+    app::{format_bytes, FocusTarget},
+    Action, App, Dispatcher, Message, TabId,
+};
 use crate::components::*;
+use crate::config::{KeyContext, Theme};
 
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>)
 where
     B: Backend,
 {
+    // The tab bar is shown once there is more than one tab.
+    let tab_bar_height = if app.tabs.is_empty() { 0 } else { 1 };
     let vertical = Layout::default()
-        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(tab_bar_height),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
         .direction(Direction::Vertical)
         .split(f.size());
 
-    draw_query_form(f, app, vertical[0]);
+    if !app.tabs.is_empty() {
+        draw_tab_bar(f, app, vertical[0]);
+    }
+    draw_query_form(f, app, vertical[1]);
 
     let horizontal = Layout::default()
         .constraints([Constraint::Min(8), Constraint::Length(1)].as_ref())
-        .split(vertical[1]);
+        .split(vertical[2]);
     draw_body(f, app, horizontal[0]);
     draw_status(f, app, horizontal[1]);
+
+    if app.show_help {
+        draw_help(f, app, f.size());
+    }
+    if app.palette.is_some() {
+        draw_palette(f, app, f.size());
+    }
+    if app.patterns.is_some() {
+        draw_patterns(f, app, f.size());
+    }
+    if app.time_picker.is_some() {
+        draw_time_picker(f, app, f.size());
+    }
+}
+
+fn draw_tab_bar<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let titles: Vec<Spans> = app
+        .tab_titles()
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| Spans::from(format!("{}: {}", i + 1, title)))
+        .collect();
+    let tabs = Tabs::new(titles)
+        .select(app.tab_index)
+        .style(Style::default().fg(Color::Gray))
+        .highlight_style(
+            Style::default()
+                .fg(app.theme.focused_border)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_widget(tabs, area);
 }
 
 fn draw_query_form<B, D: Dispatcher<Message = Message>>(
@@ -35,11 +88,12 @@ fn draw_query_form<B, D: Dispatcher<Message = Message>>(
 ) where
     B: Backend,
 {
-    let border_color = if app.default_query_input.is_focused() || app.duration_input.is_focused() {
-        Color::White
-    } else {
-        Color::DarkGray
-    };
+    let border_color =
+        if app.tab.default_query_input.is_focused() || app.tab.duration_input.is_focused() {
+            app.theme.focused_border
+        } else {
+            app.theme.border
+        };
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Log filter")
@@ -54,10 +108,13 @@ fn draw_query_form<B, D: Dispatcher<Message = Message>>(
         .direction(Direction::Horizontal)
         .split(area);
 
-    let query_input = InputView::new(&app.default_query_input);
+    app.areas.log_filter = inner_chunks[0];
+    app.areas.duration = inner_chunks[1];
+
+    let query_input = InputView::new(&app.tab.default_query_input);
     query_input.draw(f, inner_chunks[0]);
 
-    let duration_input = InputView::new(&app.duration_input);
+    let duration_input = InputView::new(&app.tab.duration_input);
     duration_input.draw(f, inner_chunks[1]);
 }
 
@@ -76,9 +133,17 @@ where
             .split(chunks[0]);
         {
             let chunks = Layout::default()
-                .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Percentage(app.split_percent),
+                        Constraint::Percentage(100 - app.split_percent),
+                    ]
+                    .as_ref(),
+                )
                 .direction(Direction::Horizontal)
                 .split(chunks[0]);
+            app.areas.body = chunks[0].union(chunks[1]);
+            app.areas.divider = chunks[1].x;
 
             draw_groups(f, app, chunks[0]);
             draw_logs(f, app, chunks[1]);
@@ -90,38 +155,161 @@ fn draw_status<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut
 where
     B: Backend,
 {
-    let text = if app.loading {
-        vec![Spans::from("loading...")]
-    } else if app.too_much_groups_specified {
-        vec![Spans::from("too much groups specified. uncheck some groups...")]
+    let message = if let Some(ref message) = app.status_message {
+        message.clone()
+    } else if app.tab.loading_all {
+        format!(
+            "loading older rows... {} items, {}% of the range.",
+            app.tab.logs.items.len(),
+            app.loaded_percent().unwrap_or(0)
+        )
+    } else if app.tab.loading {
+        "loading...".to_owned()
+    } else if app.tab.too_much_groups_specified {
+        "too much groups specified. uncheck some groups...".to_owned()
+    } else if app.tab.logs.is_filtering() {
+        format!(
+            "{} of {} items match.",
+            app.tab.logs.visible.len(),
+            app.tab.logs.items.len()
+        )
+    } else if app.tab.truncated {
+        format!(
+            "{} items found, older ones left out. load them with {}.",
+            app.tab.logs.items.len(),
+            app.keymap
+                .effective_keys(KeyContext::Logs, Action::LoadOlder)
+                .first()
+                .map(|k| k.to_string())
+                .unwrap_or_else(|| Action::LoadOlder.name().to_owned())
+        )
     } else {
-        vec![Spans::from(format!(
-            "{} items found.",
-            app.logs.items.len()
-        ))]
+        format!("{} items found.", app.tab.logs.items.len())
     };
+    let mut spans = vec![Span::raw(message)];
+    if app.status_message.is_none() && !app.tab.too_much_groups_specified {
+        if let Some(statistics) = statistics_text(app) {
+            let cost = app.tab.statistics.cost(app.price_per_gb);
+            let style = if cost > app.cost_warning {
+                Style::default().fg(app.theme.level_warn)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(statistics, style));
+        }
+    }
+    let text = Spans::from(spans);
     let block = Block::default()
-        .style(Style::default().bg(Color::Rgb(72, 68, 96)))
+        .style(Style::default().bg(app.theme.status_bg))
         .borders(Borders::NONE);
-    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
-    f.render_widget(paragraph, area);
+    f.render_widget(block, area);
+
+    // The hint is dropped when it does not fit next to the message.
+    let hint = app.key_hint();
+    let hint_width = hint.width() as u16;
+    let (message_area, hint_area) = if text.width() as u16 + 2 + hint_width <= area.width {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(hint_width)].as_ref())
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+    let paragraph = Paragraph::new(vec![text]).wrap(Wrap { trim: true });
+    f.render_widget(paragraph, message_area);
+
+    if let Some(hint_area) = hint_area {
+        let hint = Paragraph::new(Spans::from(Span::styled(
+            hint,
+            Style::default().fg(Color::Gray),
+        )))
+        .alignment(Alignment::Right);
+        f.render_widget(hint, hint_area);
+    }
+}
+
+/// Statistics of the running or last query, like
+/// `1234 of 56789 records matched, 1.2 GB scanned (~$0.006) in 3.4s`.
+fn statistics_text<D: Dispatcher<Message = Message>>(app: &App<D>) -> Option<String> {
+    let started = app.tab.query_started_at?;
+    let elapsed = app.tab.query_finished_at.unwrap_or_else(Instant::now) - started;
+    let statistics = &app.tab.statistics;
+    Some(format!(
+        "{} of {} records matched, {} scanned (~${:.3}) in {:.1}s",
+        statistics.records_matched as u64,
+        statistics.records_scanned as u64,
+        format_bytes(statistics.bytes_scanned),
+        statistics.cost(app.price_per_gb),
+        elapsed.as_secs_f64()
+    ))
+}
+
+fn draw_help<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let entries = app.help_entries();
+    let keys_width = entries.iter().map(|(k, _)| k.width()).max().unwrap_or(0);
+    let lines: Vec<ListItem> = entries
+        .into_iter()
+        .map(|(keys, description)| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{:width$}", keys, width = keys_width),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::raw(description),
+            ]))
+        })
+        .collect();
+
+    let width = area.width.min(72);
+    let height = area.height.min(lines.len() as u16 + 2);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Keys: {}", app.focus_state.title()))
+        .border_style(Style::default().fg(app.theme.focused_border));
+    f.render_widget(Clear, popup);
+    f.render_widget(List::new(lines).block(block), popup);
 }
 
 fn draw_groups<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
 where
     B: Backend,
 {
+    let inner_chunks = Layout::default()
+        .vertical_margin(1)
+        .horizontal_margin(1)
+        .constraints([Constraint::Length(2), Constraint::Min(1)].as_ref())
+        .direction(Direction::Vertical)
+        .split(area);
+    app.areas.group_filter = inner_chunks[0];
+    app.areas.groups = inner_chunks[1];
+
     // Draw groups
-    let filter = regex::escape(&app.group_names.filter);
-    let re = regex::Regex::new(&filter).expect("Failed to construct Regexp");
+    app.tab
+        .group_names
+        .update_offset(inner_chunks[1].height as usize);
     let groups: Vec<ListItem> = app
+        .tab
         .group_names
-        .items
+        .filtered
         .iter()
-        .filter(|name| re.is_match(name))
+        .skip(app.tab.group_names.offset)
         .map(|item| {
             let style = Style::default();
-            let mut line = Checkbox::from(app.group_names.selected.contains(item)).render();
+            let mut line = Checkbox::from(app.tab.group_names.selected.contains(item))
+                .checked_color(app.theme.checkbox)
+                .render();
             line.0.extend(vec![Span::raw(" "), Span::raw(item)]);
             ListItem::new(line).style(style)
         })
@@ -129,9 +317,9 @@ where
 
     let border_color =
         if app.focus_state == FocusTarget::Groups || app.focus_state == FocusTarget::GroupFilter {
-            Color::White
+            app.theme.focused_border
         } else {
-            Color::DarkGray
+            app.theme.border
         };
 
     f.render_widget(
@@ -142,21 +330,14 @@ where
         area,
     );
 
-    let inner_chunks = Layout::default()
-        .vertical_margin(1)
-        .horizontal_margin(1)
-        .constraints([Constraint::Length(2), Constraint::Min(1)].as_ref())
-        .direction(Direction::Vertical)
-        .split(area);
-
-    let input = InputView::new(&app.group_filter_input);
+    let input = InputView::new(&app.tab.group_filter_input);
     input.draw(f, inner_chunks[0]);
 
     let groups = List::new(groups)
         .highlight_style(if app.focus_state == FocusTarget::Groups {
             Style::default()
                 .add_modifier(Modifier::BOLD)
-                .bg(Color::Rgb(72, 68, 96))
+                .bg(app.theme.selection_bg)
         } else {
             Style::default()
         })
@@ -166,7 +347,16 @@ where
             " "
         });
 
-    f.render_stateful_widget(groups, inner_chunks[1], &mut app.group_names.state);
+    // The list is already scrolled by `offset`, so select relative to it.
+    let mut state = ListState::default();
+    state.select(
+        app.tab
+            .group_names
+            .state
+            .selected()
+            .map(|i| i - app.tab.group_names.offset),
+    );
+    f.render_stateful_widget(groups, inner_chunks[1], &mut state);
 }
 
 fn draw_logs<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
@@ -177,46 +367,460 @@ where
         .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
         .direction(Direction::Vertical)
         .split(area);
+    app.areas.find = inner_chunks[1];
 
-    let input = InputView::new(&app.find_string_input);
+    let input = InputView::new(&app.tab.find_string_input).title(find_title(app));
     input.draw(f, inner_chunks[1]);
 
+    let mut list_area = inner_chunks[0];
+    let mut title = "Logs ".to_owned();
+    if let Some((other, first)) = app.shown_split() {
+        let stacked = app.split.map(|s| s.stacked).unwrap_or_default();
+        let panes = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(if stacked {
+                Direction::Vertical
+            } else {
+                Direction::Horizontal
+            })
+            .split(inner_chunks[0]);
+        let (mine, theirs) = if first {
+            (panes[0], panes[1])
+        } else {
+            (panes[1], panes[0])
+        };
+        list_area = mine;
+        title = format!("Logs: {} ", app.tab.title());
+        draw_other_logs(f, app, other, theirs);
+    }
+
     let border_color = if app.focus_state == FocusTarget::Logs {
-        Color::White
+        app.theme.focused_border
     } else {
-        Color::DarkGray
+        app.theme.border
     };
 
+    if let Some(level) = app.tab.logs.min_level() {
+        title += &format!("≥{} ", level);
+    }
+    if app.tab.logs.collapse() != Collapse::Off {
+        title += &format!("×{} ", app.tab.logs.collapse());
+    }
+    if let Some(side) = app.tab.logs.side() {
+        title += &format!("±{} ", side);
+    }
+    if let Some(pattern) = app.tab.logs.pattern() {
+        let pattern: String = pattern.chars().take(32).collect();
+        title += &format!("~\"{}\" ", pattern);
+    }
+    let mut title_spans = vec![Span::raw(title.clone())];
+    for (p, color) in app
+        .tab
+        .logs
+        .pinned()
+        .iter()
+        .zip(app.theme.pinned.iter().cycle())
+    {
+        title_spans.push(Span::styled(
+            p.label(),
+            Style::default().fg(Color::Black).bg(*color),
+        ));
+        title_spans.push(Span::raw(" "));
+    }
     let log_block = Block::default()
         .borders(Borders::ALL)
-        .title("Logs")
+        .title(Spans::from(title_spans))
         .border_style(Style::default().fg(border_color));
+    app.areas.logs = log_block.inner(list_area);
+    // The title starts after the corner of the border.
+    let legend_x = list_area.x + 1 + title.width() as u16;
+    app.areas.legend = Rect {
+        x: legend_x,
+        y: list_area.y,
+        width: list_area.right().saturating_sub(legend_x + 1),
+        height: 1,
+    };
+
+    let loading = app.tab.loading;
+    draw_log_rows(
+        f,
+        &mut app.tab.logs,
+        loading,
+        &app.theme,
+        log_block,
+        list_area,
+    );
+}
+
+/// Logs of tab `id` in the other pane of the split.
+fn draw_other_logs<B, D: Dispatcher<Message = Message>>(
+    f: &mut Frame<B>,
+    app: &mut App<D>,
+    id: TabId,
+    area: Rect,
+) where
+    B: Backend,
+{
+    let theme = app.theme.clone();
+    let tab = match app.tabs.iter_mut().find(|t| t.id == id) {
+        Some(tab) => tab,
+        None => return,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Logs: {} ", tab.title()))
+        .border_style(Style::default().fg(theme.border));
+    draw_log_rows(f, &mut tab.logs, tab.loading, &theme, block, area);
+}
 
-    if app.loading {
+fn draw_log_rows<B, D: Dispatcher<Message = Message>>(
+    f: &mut Frame<B>,
+    logs: &mut LogListModel<D>,
+    loading: bool,
+    theme: &Theme,
+    block: Block,
+    area: Rect,
+) where
+    B: Backend,
+{
+    // Rows loaded so far stay shown while older ones load.
+    if loading && logs.items.is_empty() {
         let text = vec![Spans::from("loading...")];
-        let paragraph = Paragraph::new(text)
-            .block(log_block)
-            .wrap(Wrap { trim: true });
+        let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
         return;
     }
 
-    if app.logs.items.is_empty() {
-        let text = vec![Spans::from("No items")];
-        let paragraph = Paragraph::new(text)
-            .block(log_block)
-            .wrap(Wrap { trim: true });
+    if logs.visible.is_empty() {
+        let text = vec![Spans::from(if logs.items.is_empty() {
+            "No items"
+        } else {
+            "No matching items"
+        })];
+        let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
         return;
     }
 
-    let logs = LogList::new(&app.logs.items)
-        .block(log_block)
+    let items = &logs.items;
+    let summaries = &logs.summaries;
+    let rows = logs
+        .visible
+        .iter()
+        .map(|i| summaries.get(i).unwrap_or(&items[*i]))
+        .collect();
+    let list = LogList::new(rows)
+        .block(block)
         .highlight_style(
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .fg(Color::White)
-                .bg(Color::Rgb(72, 68, 96)),
+                .bg(theme.selection_bg),
+        )
+        .find_style(Style::default().fg(theme.find_fg).bg(theme.find_bg))
+        .pinned_styles(
+            theme
+                .pinned
+                .iter()
+                .map(|c| Style::default().fg(Color::Black).bg(*c))
+                .collect(),
+        )
+        .side_styles(
+            Style::default().fg(Color::Black).bg(theme.diff_only_a),
+            Style::default().fg(Color::Black).bg(theme.diff_only_b),
+            Style::default().fg(Color::DarkGray),
         );
-    f.render_stateful_widget(logs, inner_chunks[0], &mut app.logs.state);
+    f.render_stateful_widget(list, area, &mut logs.state);
+}
+
+/// Flags of the find mode, lit when enabled, followed by the find error if any.
+fn find_title<D: Dispatcher<Message = Message>>(app: &App<D>) -> Spans<'static> {
+    let mode = app.tab.logs.find_mode();
+    let flag = |enabled: bool, label: &str| {
+        Span::styled(
+            format!(" {} ", label),
+            if enabled {
+                Style::default().fg(app.theme.find_fg).bg(app.theme.find_bg)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            },
+        )
+    };
+    let mut spans = vec![
+        flag(mode.regex, ".*"),
+        flag(mode.case_sensitive, "Aa"),
+        flag(mode.whole_word, "\\b"),
+    ];
+    if let Some(e) = app.tab.logs.find_error() {
+        spans.push(Span::styled(
+            format!(" {} ", e),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    Spans::from(spans)
+}
+
+fn draw_palette<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let theme = app.theme.clone();
+    let palette = match app.palette {
+        Some(ref mut palette) => palette,
+        None => return,
+    };
+
+    let width = area.width.min(72);
+    let height = area.height.min(16);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.min(3),
+        width,
+        height: height.min(area.height.saturating_sub(3)),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Commands")
+        .border_style(Style::default().fg(theme.focused_border));
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    let inner_chunks = Layout::default()
+        .vertical_margin(1)
+        .horizontal_margin(1)
+        .constraints([Constraint::Length(2), Constraint::Min(1)].as_ref())
+        .direction(Direction::Vertical)
+        .split(popup);
+
+    let input = InputView::new(&palette.input);
+    input.draw(f, inner_chunks[0]);
+
+    let list_width = inner_chunks[1].width as usize;
+    let items: Vec<ListItem> = palette
+        .filtered
+        .iter()
+        .map(|i| {
+            let command = &palette.commands[*i];
+            let padding = list_width
+                .saturating_sub(command.label.width() + command.keys.width() + 2)
+                .max(1);
+            ListItem::new(Spans::from(vec![
+                Span::raw(command.label.clone()),
+                Span::raw(" ".repeat(padding)),
+                Span::styled(command.keys.clone(), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(theme.selection_bg),
+        )
+        .highlight_symbol("▸");
+    f.render_stateful_widget(list, inner_chunks[1], &mut palette.state);
+}
+
+fn draw_patterns<B, D: Dispatcher<Message = Message>>(
+    f: &mut Frame<B>,
+    app: &mut App<D>,
+    area: Rect,
+) where
+    B: Backend,
+{
+    let theme = app.theme.clone();
+    let patterns = match app.patterns {
+        Some(ref mut patterns) => patterns,
+        None => return,
+    };
+
+    let width = area.width.min(120);
+    let height = area.height.saturating_sub(4);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.min(2),
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Patterns of {} lines (enter: show its lines)",
+            patterns.clusters.iter().map(|c| c.count).sum::<usize>()
+        ))
+        .border_style(Style::default().fg(theme.focused_border));
+    f.render_widget(Clear, popup);
+
+    let count_width = patterns
+        .clusters
+        .first()
+        .map(|c| c.count.to_string().len())
+        .unwrap_or(1);
+    let items: Vec<ListItem> = patterns
+        .clusters
+        .iter()
+        .map(|c| {
+            ListItem::new(vec![
+                Spans::from(vec![
+                    Span::styled(
+                        format!("{:>width$} ", c.count, width = count_width),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(c.template.clone()),
+                ]),
+                Spans::from(vec![
+                    Span::raw(" ".repeat(count_width + 1)),
+                    Span::styled(c.sample.clone(), Style::default().fg(Color::DarkGray)),
+                ]),
+            ])
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(theme.selection_bg))
+        .highlight_symbol("▸");
+    f.render_stateful_widget(list, popup, &mut patterns.state);
+}
+
+fn draw_time_picker<B, D: Dispatcher<Message = Message>>(
+    f: &mut Frame<B>,
+    app: &mut App<D>,
+    area: Rect,
+) where
+    B: Backend,
+{
+    let theme = app.theme.clone();
+    let picker = match app.time_picker {
+        Some(ref mut picker) => picker,
+        None => return,
+    };
+
+    let width = area.width.min(60);
+    let height = area.height.min(15);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Time range")
+        .border_style(Style::default().fg(theme.focused_border));
+    let inner = block.inner(popup);
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    let rows = Layout::default()
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .direction(Direction::Vertical)
+        .split(inner);
+    let columns = Layout::default()
+        .constraints([Constraint::Length(8), Constraint::Min(1)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(rows[0]);
+
+    let active = Style::default().bg(theme.selection_bg);
+    let presets: Vec<ListItem> = PRESETS.iter().map(|p| ListItem::new(*p)).collect();
+    let presets = List::new(presets)
+        .highlight_style(if picker.field == PickerField::Presets {
+            active.add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        })
+        .highlight_symbol("▸");
+    f.render_stateful_widget(presets, columns[0], &mut picker.presets);
+
+    let time_line = |label: &'static str, field: PickerField, time: &chrono::NaiveDateTime| {
+        let editing = picker.field == field;
+        let mut spans = vec![Span::styled(
+            label,
+            if editing {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            },
+        )];
+        let parts = [
+            (TimePart::Year, "%Y", "-"),
+            (TimePart::Month, "%m", "-"),
+            (TimePart::Day, "%d", " "),
+            (TimePart::Hour, "%H", ":"),
+            (TimePart::Minute, "%M", ""),
+        ];
+        for (part, format, separator) in parts.iter() {
+            let style = if editing && picker.part == *part {
+                active
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(time.format(format).to_string(), style));
+            spans.push(Span::raw(*separator));
+        }
+        Spans::from(spans)
+    };
+    let mut lines = vec![
+        time_line("Start  ", PickerField::Start, &picker.start),
+        time_line("End    ", PickerField::End, &picker.end),
+        Spans::from(""),
+    ];
+    lines.extend(calendar(
+        picker.editing().unwrap_or(picker.start).date(),
+        active,
+    ));
+    f.render_widget(Paragraph::new(lines), columns[1]);
+
+    let preview = match picker.range() {
+        Ok((start, end)) => Span::styled(
+            format!(
+                "{} - {} ({})",
+                start.format("%Y-%m-%d %H:%M"),
+                end.format("%Y-%m-%d %H:%M"),
+                picker.zone
+            ),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Err(e) => Span::styled(e, Style::default().fg(Color::Red)),
+    };
+    f.render_widget(Paragraph::new(Spans::from(preview)), rows[1]);
+}
+
+/// Month of `date` as a grid of days from Monday, with `date` in `style`.
+fn calendar(date: chrono::NaiveDate, style: Style) -> Vec<Spans<'static>> {
+    use chrono::Datelike;
+
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            date.format("%B %Y").to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(Span::styled(
+            "Mo Tu We Th Fr Sa Su",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let first = date.with_day(1).unwrap_or(date);
+    let mut week: Vec<Span> = vec![Span::raw(
+        "   ".repeat(first.weekday().num_days_from_monday() as usize),
+    )];
+    for day in 1..=days_in_month(date.year(), date.month()) {
+        let text = format!("{:>2}", day);
+        week.push(if day == date.day() {
+            Span::styled(text, style)
+        } else {
+            Span::raw(text)
+        });
+        week.push(Span::raw(" "));
+        let is_sunday = first
+            .with_day(day)
+            .map(|d| d.weekday() == chrono::Weekday::Sun)
+            .unwrap_or(false);
+        if is_sunday {
+            lines.push(Spans::from(std::mem::take(&mut week)));
+        }
+    }
+    if !week.is_empty() {
+        lines.push(Spans::from(week));
+    }
+    lines
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -8,6 +10,7 @@ impl GroupsClient for Client {
     async fn get_group_names(&self) -> Result<GetGroupsOutput> {
         log::debug!("get group names");
         let mut items: Vec<String> = vec![];
+        let mut sizes: HashMap<String, GroupSize> = HashMap::new();
         let mut next_token: Option<String> = None;
         loop {
             let res = self
@@ -25,15 +28,20 @@ impl GroupsClient for Client {
 
             let res = res.unwrap();
 
-            items.extend(
-                res.log_groups
-                    .unwrap_or_default()
-                    .into_iter()
-                    .filter_map(|g| g.log_group_name),
-            );
+            for group in res.log_groups.unwrap_or_default() {
+                if let Some(name) = group.log_group_name {
+                    let size = GroupSize {
+                        stored_bytes: group.stored_bytes.unwrap_or_default(),
+                        creation_time: group.creation_time.unwrap_or_default(),
+                        retention_in_days: group.retention_in_days,
+                    };
+                    sizes.insert(name.clone(), size);
+                    items.push(name);
+                }
+            }
 
             if res.next_token.is_none() {
-                return Ok(GetGroupsOutput { items });
+                return Ok(GetGroupsOutput { items, sizes });
             }
 
             next_token = res.next_token;
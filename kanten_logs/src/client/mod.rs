@@ -2,15 +2,25 @@ mod group;
 mod query;
 mod types;
 
+pub use query::DEFAULT_LIMIT;
 pub use types::*;
 
 #[derive(Debug, Clone)]
 pub struct Client {
     client: cloudwatchlogs::Client,
+    limit: i32,
 }
 
 impl Client {
     pub fn new(client: cloudwatchlogs::Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = limit;
+        self
     }
 }
@@ -6,7 +6,7 @@ use cloudwatchlogs::model::{QueryStatus, ResultField};
 
 use super::*;
 
-const DEFAULT_LIMIT: i32 = 10_000;
+pub const DEFAULT_LIMIT: i32 = 10_000;
 
 #[async_trait]
 impl FilterLogClient for Client {
@@ -32,14 +32,15 @@ impl QueryClient for Client {
         log::trace!("start query");
         // The list of log groups to be queried. You can include up to 20 log groups.
         // See also https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_StartQuery.html
+        let query_string = input.query_string();
         let res = self
             .client
             .start_query()
             .set_log_group_names(Some(input.groups))
             .start_time(input.start)
             .end_time(input.end)
-            .query_string(format!("fields @timestamp, @message, @log | sort @timestamp desc | filter @message like /{}/", input.filter))
-            .limit(DEFAULT_LIMIT)
+            .query_string(query_string)
+            .limit(self.limit)
             .send()
             .await?;
         log::trace!("start query response is {:?}", res);
@@ -68,6 +69,14 @@ impl QueryClient for Client {
             .context("failed to get query result.")?;
 
         let items = res.results.expect("there is no results.");
+        let statistics = res
+            .statistics
+            .map(|s| QueryStatistics {
+                records_matched: s.records_matched,
+                records_scanned: s.records_scanned,
+                bytes_scanned: s.bytes_scanned,
+            })
+            .unwrap_or_default();
 
         if let Some(status) = res.status {
             log::trace!("response status is {:?}", &status);
@@ -76,29 +85,37 @@ impl QueryClient for Client {
                 .map(|item| {
                     let mut message = String::default();
                     let mut timestamp = String::default();
+                    let mut fields = vec![];
                     for ResultField { value, field, .. } in item {
                         let field = field.unwrap();
+                        let value = value.unwrap_or_default();
                         match field.as_str() {
-                            "@timestamp" => timestamp = value.unwrap(),
-                            "@message" => message = value.unwrap(),
+                            "@timestamp" => timestamp = value.clone(),
+                            "@message" => message = value.clone(),
                             _ => {}
                         }
+                        fields.push((field, value));
+                    }
+                    SearchResultItem {
+                        timestamp,
+                        message,
+                        fields,
                     }
-                    SearchResultItem { timestamp, message }
                 })
                 .collect();
 
             // Complete
             if status == QueryStatus::Complete {
-                return Ok(SearchResult::Complete(items));
+                return Ok(SearchResult::Complete(items, statistics));
             }
 
             // Running
-            if status == QueryStatus::Running && items.len() >= DEFAULT_LIMIT as usize {
-                return Ok(SearchResult::Complete(items));
+            if status == QueryStatus::Running && items.len() >= self.limit as usize {
+                return Ok(SearchResult::Complete(items, statistics));
             }
             // TODO: error handling
+            return Ok(SearchResult::Running(query_id.clone(), items, statistics));
         }
-        Ok(SearchResult::Running(query_id.clone()))
+        Ok(SearchResult::Running(query_id.clone(), vec![], statistics))
     }
 }
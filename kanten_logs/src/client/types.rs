@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -19,6 +21,30 @@ impl From<&QueryId> for String {
 #[derive(Debug, PartialEq, Clone)]
 pub struct GetGroupsOutput {
     pub items: Vec<String>,
+    pub sizes: HashMap<String, GroupSize>,
+}
+
+/// Stored size of a log group, to estimate how much a query over it scans.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct GroupSize {
+    pub stored_bytes: i64,
+    /// Epoch milliseconds.
+    pub creation_time: i64,
+    pub retention_in_days: Option<i32>,
+}
+
+impl GroupSize {
+    /// Bytes a query from `start` to `end`, in epoch seconds, scans,
+    /// assuming the stored events were ingested evenly.
+    pub fn estimate(&self, start: i64, end: i64, now: i64) -> f64 {
+        let mut oldest = self.creation_time / 1000;
+        if let Some(days) = self.retention_in_days {
+            oldest = oldest.max(now - days as i64 * 86400);
+        }
+        let span = (now - oldest).max(1);
+        let overlap = (end.min(now) - start.max(oldest)).max(0);
+        self.stored_bytes as f64 * overlap as f64 / span as f64
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -36,12 +62,32 @@ pub trait GroupsClient {
 pub struct SearchResultItem {
     pub message: String,
     pub timestamp: String,
+    /// Every field of the result row, including `@timestamp` and `@message`.
+    pub fields: Vec<(String, String)>,
+}
+
+/// Progress of a query as reported by Insights.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct QueryStatistics {
+    pub records_matched: f64,
+    pub records_scanned: f64,
+    pub bytes_scanned: f64,
+}
+
+impl QueryStatistics {
+    /// Estimated cost of the bytes scanned at `price_per_gb`.
+    pub fn cost(&self, price_per_gb: f64) -> f64 {
+        self.bytes_scanned / BYTES_PER_GB * price_per_gb
+    }
 }
 
+pub const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum SearchResult {
-    Running(QueryId),
-    Complete(Vec<SearchResultItem>),
+    /// Rows found so far, which are all rows if the query is stopped now.
+    Running(QueryId, Vec<SearchResultItem>, QueryStatistics),
+    Complete(Vec<SearchResultItem>, QueryStatistics),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -52,6 +98,34 @@ pub struct StartQueryInput {
     pub groups: Vec<String>,
 }
 
+/// Start of the query a filter is run as, followed by the filter and `/`.
+const FILTER_QUERY: &str =
+    "fields @timestamp, @message, @log | sort @timestamp desc | filter @message like /";
+
+impl StartQueryInput {
+    /// Logs Insights query the filter is run as.
+    pub fn query_string(&self) -> String {
+        format!("{}{}/", FILTER_QUERY, self.filter)
+    }
+
+    /// Whether `other` runs the same query over a range as wide, as a relative
+    /// duration run again does. Its times, parsed separately, may differ by a second.
+    pub fn is_rerun_of(&self, other: &StartQueryInput) -> bool {
+        self.groups == other.groups
+            && self.filter == other.filter
+            && ((self.end - self.start) - (other.end - other.start)).abs() <= 1
+    }
+}
+
+/// Filter of a query made by `StartQueryInput::query_string`.
+pub fn filter_of_query(query: &str) -> Option<String> {
+    query
+        .trim()
+        .strip_prefix(FILTER_QUERY)?
+        .strip_suffix('/')
+        .map(|filter| filter.to_owned())
+}
+
 #[async_trait]
 pub trait QueryClient {
     async fn start_default_query<'a>(&self, input: StartQueryInput) -> Result<QueryId>;
@@ -0,0 +1,34 @@
+use std::{
+    io::{stdout, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+
+/// Most bytes of text copied at once. Terminals and tmux drop OSC 52
+/// sequences longer than about 100 KB, which this is once base64 encoded.
+pub const MAX_COPY_BYTES: usize = 74 * 1024;
+
+/// Copy `text` to the system clipboard of the terminal with the OSC 52 escape sequence.
+/// It works over SSH as long as the terminal supports OSC 52.
+///
+/// Text longer than [`MAX_COPY_BYTES`] is cut to it, and `false` is returned.
+pub fn copy(text: &str) -> Result<bool> {
+    let mut len = text.len().min(MAX_COPY_BYTES);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", base64::encode(&text[..len]))?;
+    out.flush()?;
+    Ok(len == text.len())
+}
+
+/// Write `text` to `yank.txt` in the cache dir, for when it could not be copied
+/// whole.
+pub fn write_fallback(text: &str) -> Result<PathBuf> {
+    let mut path = crate::get_app_cache_path()?;
+    path.push("yank.txt");
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
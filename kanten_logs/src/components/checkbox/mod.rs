@@ -7,11 +7,20 @@ use super::inline_component::*;
 
 pub struct Checkbox {
     pub checked: bool,
+    pub checked_color: Color,
 }
 
 impl Checkbox {
     pub fn from(checked: bool) -> Checkbox {
-        Self { checked }
+        Self {
+            checked,
+            checked_color: Color::Rgb(43, 116, 100),
+        }
+    }
+
+    pub fn checked_color(mut self, color: Color) -> Checkbox {
+        self.checked_color = color;
+        self
     }
 }
 
@@ -20,7 +29,7 @@ impl InlineComponent for Checkbox {
         if self.checked {
             Spans::from(vec![Span::styled(
                 "◉",
-                Style::default().fg(Color::Rgb(43, 116, 100)),
+                Style::default().fg(self.checked_color),
             )])
         } else {
             Spans::from(vec![Span::raw("◯")])
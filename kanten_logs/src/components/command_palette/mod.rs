@@ -0,0 +1,90 @@
+use tui::{
+    style::Style,
+    widgets::{Block, Borders, ListState},
+};
+
+use super::InputModel;
+use crate::app::Action;
+use crate::models::fuzzy_match;
+
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub action: Action,
+    pub label: String,
+    pub keys: String,
+}
+
+pub struct CommandPalette<'a> {
+    pub input: InputModel<'a>,
+    pub state: ListState,
+    pub commands: Vec<Command>,
+    /// Indices of `commands` matching the input, best first.
+    pub filtered: Vec<usize>,
+}
+
+impl<'a> CommandPalette<'a> {
+    pub fn new(commands: Vec<Command>) -> Self {
+        let mut input = InputModel::new().set_placeholder("Type a command").block(
+            Block::default()
+                .borders(Borders::BOTTOM)
+                .border_style(Style::default()),
+        );
+        input.focus();
+        let mut palette = CommandPalette {
+            input,
+            state: ListState::default(),
+            commands,
+            filtered: vec![],
+        };
+        palette.update_filter();
+        palette
+    }
+
+    fn update_filter(&mut self) {
+        let pattern = self.input.value();
+        let mut scored: Vec<(i64, usize)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let text = format!("{} {}", c.label, c.action.name());
+                fuzzy_match(pattern, &text).map(|score| (score, i))
+            })
+            .collect();
+        // Stable sort keeps the declaration order for equal scores.
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+        self.filtered = scored.into_iter().map(|(_, i)| i).collect();
+        self.state.select(if self.filtered.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    pub fn selected(&self) -> Option<Action> {
+        self.state
+            .selected()
+            .and_then(|i| self.filtered.get(i))
+            .map(|i| self.commands[*i].action)
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        let len = self.filtered.len();
+        match action {
+            Action::Down => {
+                if let Some(i) = self.state.selected() {
+                    self.state.select(Some((i + 1) % len));
+                }
+            }
+            Action::Up => {
+                if let Some(i) = self.state.selected() {
+                    self.state.select(Some((i + len - 1) % len));
+                }
+            }
+            _ => {
+                self.input.on_action(action);
+                self.update_filter();
+            }
+        }
+    }
+}
@@ -1,11 +1,12 @@
+use crate::app::{self, Action};
 use std::collections::BTreeSet;
 use tui::widgets::ListState;
-use crate::app;
-
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+#[derive(Clone)]
 pub struct GroupList {
     pub state: ListState,
+    /// Index of the first visible row of `filtered`.
+    pub offset: usize,
     pub items: Vec<String>,
     pub selected: BTreeSet<String>,
     pub filter: String,
@@ -43,6 +44,7 @@ impl GroupList {
 
         GroupList {
             state,
+            offset: 0,
             items,
             selected,
             filtered,
@@ -85,41 +87,25 @@ impl GroupList {
     //         .collect()
     // }
 
-    pub fn on_key(&mut self, key: KeyEvent) {
-        match key {
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-            } => {
-                log::debug!("on group_list key {:?}", self.state.selected());
-                if let Some(index) = self.state.selected() {
-                    let name = &self.filtered[index];
-                    log::debug!("name {:?}", name);
-                    if self.selected.contains(name) {
-                        self.selected.remove(name);
-                    } else {
-                        self.selected.insert(name.clone());
-                    }
-                }
-            }
-            // down
-            KeyEvent {
-                code: KeyCode::Char('n'),
-                modifiers: KeyModifiers::CONTROL,
+    pub fn toggle_selected(&mut self) {
+        log::debug!("on group_list key {:?}", self.state.selected());
+        if let Some(index) = self.state.selected() {
+            let name = &self.filtered[index];
+            log::debug!("name {:?}", name);
+            if self.selected.contains(name) {
+                self.selected.remove(name);
+            } else {
+                self.selected.insert(name.clone());
             }
-            | KeyEvent {
-                code: KeyCode::Down,
-                modifiers: KeyModifiers::NONE,
-            } => self.next(),
-            // up
-            KeyEvent {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::NONE,
-            } => self.previous(),
+        }
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.next(),
+            Action::Up => self.previous(),
+            Action::Top => self.first(),
+            Action::Bottom => self.last(),
             _ => {}
         }
     }
@@ -152,6 +138,36 @@ impl GroupList {
         self.state.select(Some(i));
     }
 
+    /// Scroll so that the selected row is visible in `height` rows.
+    pub fn update_offset(&mut self, height: usize) {
+        let selected = self.state.selected().unwrap_or(0);
+        if selected < self.offset {
+            self.offset = selected;
+        } else if height > 0 && selected >= self.offset + height {
+            self.offset = selected + 1 - height;
+        }
+        self.offset = self.offset.min(self.filtered.len().saturating_sub(1));
+    }
+
+    /// Index in `filtered` of the `row`th visible row.
+    pub fn index_at(&self, row: usize) -> Option<usize> {
+        let index = self.offset + row;
+        if index < self.filtered.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    pub fn first(&mut self) {
+        self.state.select(Some(0));
+    }
+
+    pub fn last(&mut self) {
+        self.state
+            .select(Some(self.filtered.len().saturating_sub(1)));
+    }
+
     // pub fn unselect(&mut self) {
     //     self.state.select(None);
     // }
@@ -3,15 +3,15 @@ use tui::{
     buffer::Buffer,
     layout::{Margin, Rect},
     style::{Color, Style},
+    text::Spans,
     widgets::{Block, Widget},
     Frame,
 };
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::BlockComponent;
+use crate::app::Action;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Position {
@@ -59,6 +59,12 @@ impl<'a> InputModel<'a> {
         self
     }
 
+    /// Replace the value, moving the cursor to its end.
+    pub fn replace(&mut self, v: impl Into<String>) {
+        self.value = v.into();
+        self.cursor_position.x = UnicodeSegmentation::graphemes(self.value(), true).count() as u16;
+    }
+
     pub fn value(&self) -> &str {
         self.value.as_str()
     }
@@ -107,82 +113,36 @@ impl<'a> InputModel<'a> {
         self.focused = false;
     }
 
-    pub fn on_key(&mut self, key: KeyEvent) {
-        match key {
-            // start
-            KeyEvent {
-                code: KeyCode::Char('a'),
-                modifiers: KeyModifiers::CONTROL,
-            } => {
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::CursorStart => {
                 self.cursor_position.x = 0;
             }
-            // end
-            KeyEvent {
-                code: KeyCode::Char('e'),
-                modifiers: KeyModifiers::CONTROL,
-            } => {
+            Action::CursorEnd => {
                 let size = UnicodeSegmentation::graphemes(self.value(), true).count() as u16;
                 self.cursor_position.x = size;
             }
-            // ->
-            KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::NONE,
-            } => {
+            Action::CursorForward => {
                 let size = UnicodeSegmentation::graphemes(self.value(), true).count() as u16;
                 if self.cursor_position.x == size {
                     return;
                 }
                 self.cursor_position.x += 1;
             }
-            // <-
-            KeyEvent {
-                code: KeyCode::Char('b'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::NONE,
-            } => self.cursor_position.x = self.cursor_position.x.saturating_sub(1),
-            // input
-            KeyEvent {
-                code: KeyCode::Char(c),
-                modifiers: KeyModifiers::NONE,
+            Action::CursorBackward => {
+                self.cursor_position.x = self.cursor_position.x.saturating_sub(1)
             }
-            | KeyEvent {
-                code: KeyCode::Char(c),
-                modifiers: KeyModifiers::SHIFT,
-            } => {
+            Action::Insert(c) => {
                 self.value.insert(self.cursor_position.x as usize, c);
                 self.cursor_position.x += 1;
             }
-            // delete
-            KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Delete,
-                modifiers: KeyModifiers::NONE,
-            } => {
+            Action::DeleteForward => {
                 if self.value.is_empty() {
                     return;
                 }
                 self.value.remove(self.cursor_position.x as usize);
             }
-            // backspace
-            KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: KeyModifiers::NONE,
-            } => {
+            Action::DeleteBackward => {
                 if self.cursor_position.x == 0 {
                     return;
                 }
@@ -197,11 +157,18 @@ impl<'a> InputModel<'a> {
 #[derive(Debug, Clone, PartialEq)]
 pub struct InputView<'a> {
     model: &'a InputModel<'a>,
+    title: Option<Spans<'a>>,
 }
 
 impl<'a> InputView<'a> {
     pub fn new(model: &'a InputModel) -> Self {
-        Self { model }
+        Self { model, title: None }
+    }
+
+    /// Title drawn on the block of the model, e.g. to show state owned by someone else.
+    pub fn title(mut self, title: Spans<'a>) -> Self {
+        self.title = Some(title);
+        self
     }
 }
 
@@ -232,6 +199,10 @@ impl<'a> Widget for InputView<'a> {
         } else {
             self.model.block.clone()
         };
+        let block = match self.title {
+            Some(title) => block.map(|b| b.title(title)),
+            None => block,
+        };
         let area = match block {
             Some(b) => {
                 let inner_area = b.inner(area);
@@ -0,0 +1,77 @@
+use std::{collections::HashMap, fmt};
+
+use super::LogListItem;
+use crate::models::template;
+
+/// How repeated messages are collapsed into one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collapse {
+    Off,
+    /// Runs of the same message.
+    Consecutive,
+    /// The same message anywhere in the results.
+    Identical,
+    /// Messages differing only in numbers, UUIDs and hex anywhere in the results.
+    Similar,
+}
+
+impl Collapse {
+    pub fn next(self) -> Collapse {
+        match self {
+            Collapse::Off => Collapse::Consecutive,
+            Collapse::Consecutive => Collapse::Identical,
+            Collapse::Identical => Collapse::Similar,
+            Collapse::Similar => Collapse::Off,
+        }
+    }
+
+    /// Group `indices` of `items`, keeping the order of the first item of each group.
+    pub fn group(self, items: &[LogListItem], indices: Vec<usize>) -> Vec<Vec<usize>> {
+        match self {
+            Collapse::Off => indices.into_iter().map(|i| vec![i]).collect(),
+            Collapse::Consecutive => {
+                let mut groups: Vec<Vec<usize>> = vec![];
+                for i in indices {
+                    match groups.last_mut() {
+                        Some(group) if items[group[0]].message() == items[i].message() => {
+                            group.push(i)
+                        }
+                        _ => groups.push(vec![i]),
+                    }
+                }
+                groups
+            }
+            Collapse::Identical | Collapse::Similar => {
+                let mut groups: Vec<Vec<usize>> = vec![];
+                let mut index_of: HashMap<String, usize> = HashMap::new();
+                for i in indices {
+                    let key = if self == Collapse::Similar {
+                        template::mask(items[i].message())
+                    } else {
+                        items[i].message().to_owned()
+                    };
+                    match index_of.get(&key) {
+                        Some(g) => groups[*g].push(i),
+                        None => {
+                            index_of.insert(key, groups.len());
+                            groups.push(vec![i]);
+                        }
+                    }
+                }
+                groups
+            }
+        }
+    }
+}
+
+impl fmt::Display for Collapse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Collapse::Off => "off",
+            Collapse::Consecutive => "consecutive",
+            Collapse::Identical => "identical",
+            Collapse::Similar => "similar",
+        };
+        write!(f, "{}", s)
+    }
+}
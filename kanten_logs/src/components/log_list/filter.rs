@@ -0,0 +1,81 @@
+use regex::Regex;
+
+/// How the text typed into "Find string in logs" is matched.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FindMode {
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+impl FindMode {
+    /// Regex source matching `text` in this mode, or an empty string for an empty `text`.
+    pub fn pattern(&self, text: &str) -> Result<String, regex::Error> {
+        if text.is_empty() {
+            return Ok(String::new());
+        }
+        let mut pattern = if self.regex {
+            // Check it alone, wrapping it below could turn an invalid regex into a valid one.
+            Regex::new(text)?;
+            text.to_owned()
+        } else {
+            regex::escape(text)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        Ok(if self.case_sensitive {
+            format!("(?:{})", pattern)
+        } else {
+            format!("(?i:{})", pattern)
+        })
+    }
+}
+
+/// Terms typed into "Find string in logs", used to narrow the visible rows.
+/// Terms are separated by whitespace and AND-ed. A term starting with `!`
+/// matches rows not containing it.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    re: Regex,
+    inverse: bool,
+}
+
+impl Filter {
+    pub fn parse(s: &str, mode: FindMode) -> Result<Self, regex::Error> {
+        let terms = s
+            .split_whitespace()
+            .filter_map(|t| match t.strip_prefix('!') {
+                Some("") => None,
+                Some(t) => Some((t, true)),
+                None => Some((t, false)),
+            })
+            .map(|(t, inverse)| {
+                Ok(Term {
+                    re: Regex::new(&mode.pattern(t)?)?,
+                    inverse,
+                })
+            })
+            .collect::<Result<_, regex::Error>>()?;
+        Ok(Filter { terms })
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        self.terms.iter().all(|t| t.re.is_match(text) != t.inverse)
+    }
+
+    /// Regex highlighting the terms a row has to contain.
+    pub fn highlight_pattern(&self) -> String {
+        self.terms
+            .iter()
+            .filter(|t| !t.inverse)
+            .map(|t| t.re.as_str())
+            .collect::<Vec<&str>>()
+            .join("|")
+    }
+}
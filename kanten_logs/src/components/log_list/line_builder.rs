@@ -1,6 +1,7 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, ops::Range};
 
-use tui::style::{Color, Style};
+use regex::Regex;
+use tui::style::Style;
 use tui::text::{Span, Spans};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
@@ -21,105 +22,66 @@ impl LineBuilder {
         text.width()
     }
 
+    /// Wrap `text` into lines of `text_area_width`, styling matches of each
+    /// highlight regex with its style. Earlier highlights win where matches overlap.
     pub fn run_composer<'a>(
         &self,
         text: &'a str,
         text_area_width: u16,
-        find_text: &str,
+        highlights: &[(&Regex, Style)],
     ) -> Vec<Spans<'a>> {
-        let key = format!("{}-{}-{}", text.to_owned(), text_area_width, find_text);
+        let key = format!(
+            "{}-{}-{:?}",
+            text.to_owned(),
+            text_area_width,
+            highlights
+                .iter()
+                .map(|(re, style)| (re.as_str(), style))
+                .collect::<Vec<_>>()
+        );
         if let Some(c) = self.lines_cache.borrow_mut().get(&key) {
             return c.clone();
         }
-        let find_text = regex::escape(find_text);
         let mut lines: Vec<Spans> = vec![];
 
         for t in text.split('\n') {
             if t.is_empty() {
                 continue;
             }
-            let re =
-                regex::Regex::new(&format!("(?i)({})", find_text)).expect("Failed to build regex");
-            let mut caps = re.find_iter(t);
-
-            if self.width(text) as u16 <= text_area_width {
-                let mut spans = vec![];
-                for t in re.split(text) {
-                    spans.push(Span::raw(t.to_owned()));
-                    if let Some(c) = caps.next() {
-                        spans.push(Span::styled(
-                            c.as_str().to_string(),
-                            Style::default()
-                                .bg(Color::Rgb(238, 173, 15))
-                                .fg(Color::Black),
-                        ));
-                    }
-                }
-                lines.push(Spans::from(spans));
-                continue;
-            }
+            let ranges = highlight_ranges(t, highlights);
+            let mut ranges = ranges.iter().peekable();
             let mut line: Vec<Span> = vec![];
-            let mut line_text = String::new();
+            let mut span_text = String::new();
+            let mut span_style = Style::default();
             let mut line_width: u16 = 0;
-            let mut cap = if find_text.is_empty() {
-                None
-            } else {
-                caps.next()
-            };
-
-            for (i, t) in UnicodeSegmentation::graphemes(text, true).enumerate() {
-                let w = self.width(t) as u16;
-
-                if let Some(c) = cap {
-                    if i == c.start() && !line_text.is_empty() {
-                        line.push(Span::styled(line_text.clone(), Style::default()));
-                        line_text = "".to_owned();
-                    }
-
-                    if i == c.end() && !line_text.is_empty() {
-                        line.push(Span::styled(
-                            line_text.clone(),
-                            Style::default()
-                                .bg(Color::Rgb(238, 173, 15))
-                                .fg(Color::Black),
-                        ));
-                        line_text = "".to_owned();
-                    }
 
-                    if i == c.end() {
-                        cap = caps.next();
-                    }
+            for (i, g) in UnicodeSegmentation::grapheme_indices(t, true) {
+                while ranges.peek().map(|(r, _)| r.end <= i).unwrap_or(false) {
+                    ranges.next();
                 }
+                let style = match ranges.peek() {
+                    Some((r, style)) if r.start <= i => *style,
+                    _ => Style::default(),
+                };
+                let w = self.width(g) as u16;
 
-                if line_width + w > text_area_width {
-                    if !line_text.is_empty() {
-                        if let Some(c) = cap {
-                            if i >= c.start() && i <= c.end() {
-                                line.push(Span::styled(
-                                    line_text.clone(),
-                                    Style::default()
-                                        .bg(Color::Rgb(238, 173, 15))
-                                        .fg(Color::Black),
-                                ));
-                            } else {
-                                line.push(Span::styled(line_text.clone(), Style::default()));
-                            }
-                        } else {
-                            line.push(Span::styled(line_text.clone(), Style::default()));
-                        }
+                if line_width + w > text_area_width && line_width > 0 {
+                    if !span_text.is_empty() {
+                        line.push(Span::styled(std::mem::take(&mut span_text), span_style));
                     }
-                    lines.push(Spans::from(line));
-                    line = vec![];
-                    line_text = t.to_string();
-                    line_width = w;
-                } else {
-                    line_text += t;
-                    line_width += w;
+                    lines.push(Spans::from(std::mem::take(&mut line)));
+                    line_width = 0;
+                }
+                if style != span_style && !span_text.is_empty() {
+                    line.push(Span::styled(std::mem::take(&mut span_text), span_style));
                 }
+                span_style = style;
+                span_text += g;
+                line_width += w;
             }
 
-            if !line_text.is_empty() {
-                line.push(Span::styled(line_text, Style::default()));
+            if !span_text.is_empty() {
+                line.push(Span::styled(span_text, span_style));
             }
 
             if !line.is_empty() {
@@ -130,3 +92,29 @@ impl LineBuilder {
         lines
     }
 }
+
+/// Non overlapping, non empty byte ranges of `text` to style, in order.
+/// Where matches overlap, bytes take the style of the earliest highlight.
+fn highlight_ranges(text: &str, highlights: &[(&Regex, Style)]) -> Vec<(Range<usize>, Style)> {
+    // Index of the highlight styling each byte.
+    let mut owners: Vec<Option<usize>> = vec![None; text.len()];
+    for (h, (re, _)) in highlights.iter().enumerate() {
+        for m in re.find_iter(text) {
+            for owner in owners[m.range()].iter_mut().filter(|o| o.is_none()) {
+                *owner = Some(h);
+            }
+        }
+    }
+    let mut ranges: Vec<(Range<usize>, Style)> = vec![];
+    let mut start = 0;
+    for i in 1..=owners.len() {
+        if i < owners.len() && owners[i] == owners[start] {
+            continue;
+        }
+        if let Some(h) = owners[start] {
+            ranges.push((start..i, highlights[h].1));
+        }
+        start = i;
+    }
+    ranges
+}
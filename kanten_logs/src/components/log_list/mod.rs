@@ -1,31 +1,80 @@
+mod collapse;
+mod filter;
 mod line_builder;
 
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+};
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
 use tui::{
     buffer::Buffer,
     layout::Rect,
     style::Style,
     widgets::{Block, StatefulWidget, Widget},
 };
+use unicode_width::UnicodeWidthStr;
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
-use crate::app::{Dispatcher, Message};
+use crate::app::{Action, Dispatcher, Message, TabId};
+use crate::models::{
+    parse_timestamp,
+    template::{template_of, Side},
+    Level, TimestampStyle, Zone, DEFAULT_TIME_FORMAT,
+};
 
 use self::line_builder::LineBuilder;
+pub use collapse::Collapse;
+pub use filter::{Filter, FindMode};
+
+/// Columns of the gutter of diff rows.
+const GUTTER_WIDTH: u16 = 2;
 
 pub struct LogListModel<D: Dispatcher<Message = Message>> {
     pub state: LogListState<D>,
     pub items: Vec<LogListItem>,
+    /// Indices of `items` shown in the list. `state` points into this.
+    pub visible: Vec<usize>,
+    /// Rows standing for a collapsed group, by the index of its first item.
+    pub summaries: HashMap<usize, LogListItem>,
+    collapse: Collapse,
+    /// Indices of the items of each group of repeated messages, by the index of its first item.
+    groups: HashMap<usize, Vec<usize>>,
+    /// First item of the group each grouped item belongs to.
+    group_of: HashMap<usize, usize>,
+    /// Groups shown item by item, by the index of their first item.
+    expanded: HashSet<usize>,
+    filtering: bool,
+    filter: Filter,
+    find_mode: FindMode,
+    /// Template rows have to match, picked from the pattern list.
+    pattern: Option<String>,
+    timestamp_style: TimestampStyle,
+    /// Rows below this level are hidden. Rows without a level count as info.
+    min_level: Option<Level>,
+    /// Why the find text could not be used, e.g. an invalid regex.
+    find_error: Option<String>,
+    /// Side of a diff rows have to be on.
+    side: Option<Side>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LogListState<D: Dispatcher<Message = Message>> {
     offset: usize,
     selected: Option<usize>,
+    /// Start of the range selection, if selecting.
+    anchor: Option<usize>,
     focused: bool,
     find_text: String,
+    /// Text highlighted in rows.
+    highlight: Option<Regex>,
+    /// Terms highlighted in rows on top of `highlight`, each in its own color.
+    pinned: Vec<PinnedTerm>,
     end_index: usize,
     prev_page_start_index: usize,
+    /// Tab the list belongs to, which the indices updated on render are sent for.
+    tab: TabId,
     dispatcher: D,
 }
 
@@ -34,10 +83,14 @@ impl<D: Dispatcher<Message = Message>> LogListState<D> {
         LogListState {
             offset: 0,
             selected: None,
+            anchor: None,
             focused: false,
             prev_page_start_index: 0,
             end_index: 0,
+            tab: 0,
             find_text: String::default(),
+            highlight: None,
+            pinned: vec![],
             dispatcher,
         }
     }
@@ -54,6 +107,13 @@ impl<D: Dispatcher<Message = Message>> LogListState<D> {
             self.offset = 0;
         }
     }
+
+    /// Rows between the anchor and the cursor, or only the cursor row.
+    pub fn selected_range(&self) -> Option<RangeInclusive<usize>> {
+        let selected = self.selected?;
+        let anchor = self.anchor.unwrap_or(selected);
+        Some(anchor.min(selected)..=anchor.max(selected))
+    }
 }
 
 impl<D: Dispatcher<Message = Message>> LogListModel<D> {
@@ -63,26 +123,466 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
         LogListModel {
             state,
             items: Vec::new(),
+            visible: Vec::new(),
+            summaries: HashMap::new(),
+            collapse: Collapse::Off,
+            groups: HashMap::new(),
+            group_of: HashMap::new(),
+            expanded: HashSet::new(),
+            filtering: false,
+            filter: Filter::default(),
+            find_mode: FindMode::default(),
+            pattern: None,
+            timestamp_style: TimestampStyle::new(Zone::Utc, DEFAULT_TIME_FORMAT),
+            min_level: None,
+            find_error: None,
+            side: None,
         }
     }
 
+    pub fn set_tab(&mut self, tab: TabId) {
+        self.state.tab = tab;
+    }
+
     pub fn set_find_text(&mut self, t: impl Into<String>) {
         self.state.find_text = t.into();
+        self.update_find();
+        if self.filtering {
+            self.refilter();
+        }
+    }
+
+    pub fn find_mode(&self) -> FindMode {
+        self.find_mode
+    }
+
+    pub fn set_find_mode(&mut self, mode: FindMode) {
+        self.find_mode = mode;
+        self.update_find();
+        if self.filtering {
+            self.refilter();
+        }
+    }
+
+    pub fn find_error(&self) -> Option<&str> {
+        self.find_error.as_deref()
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.filtering
+    }
+
+    /// Show only rows matching the find text, or all rows again.
+    pub fn toggle_filtering(&mut self) {
+        self.filtering = !self.filtering;
+        self.update_find();
+        self.refilter();
+    }
+
+    /// Rebuild the filter and the highlight from the find text and mode.
+    /// On error they are left as they were, except that nothing is highlighted.
+    fn update_find(&mut self) {
+        let text = &self.state.find_text;
+        let mode = self.find_mode;
+        let built = if self.filtering {
+            Filter::parse(text, mode).map(|filter| {
+                let pattern = filter.highlight_pattern();
+                (Some(filter), pattern)
+            })
+        } else {
+            mode.pattern(text).map(|pattern| (None, pattern))
+        }
+        .and_then(|(filter, pattern)| {
+            let highlight = if pattern.is_empty() {
+                None
+            } else {
+                Some(Regex::new(&pattern)?)
+            };
+            Ok((filter, highlight))
+        });
+        match built {
+            Ok((filter, highlight)) => {
+                if let Some(filter) = filter {
+                    self.filter = filter;
+                }
+                self.state.highlight = highlight;
+                self.find_error = None;
+            }
+            Err(e) => {
+                self.state.highlight = None;
+                // The last line of a syntax error describes it, the others point at it.
+                let e = e.to_string();
+                let reason = e.lines().last().unwrap_or_default();
+                self.find_error = Some(format!(
+                    "invalid regex: {}",
+                    reason.trim_start_matches("error: ")
+                ));
+            }
+        }
+    }
+
+    pub fn pinned(&self) -> &[PinnedTerm] {
+        &self.state.pinned
+    }
+
+    /// Pin the find text as a highlight, or unpin it if it is already pinned.
+    /// Returns false if there is nothing valid to pin.
+    pub fn toggle_pin(&mut self) -> bool {
+        let text = self.state.find_text.clone();
+        if let Some(i) = self.state.pinned.iter().position(|p| p.text == text) {
+            self.unpin(i);
+            return true;
+        }
+        let re = match self.find_mode.pattern(&text).map(|p| Regex::new(&p)) {
+            Ok(Ok(re)) if !text.is_empty() => re,
+            _ => return false,
+        };
+        self.state.pinned.push(PinnedTerm { text, re });
+        true
+    }
+
+    pub fn unpin(&mut self, index: usize) {
+        if index < self.state.pinned.len() {
+            self.state.pinned.remove(index);
+        }
+    }
+
+    /// Pinned term at `column` of the legend, where labels are separated by a space.
+    pub fn pinned_at(&self, column: u16) -> Option<usize> {
+        let mut left = 0;
+        for (i, p) in self.state.pinned.iter().enumerate() {
+            let right = left + p.label().width();
+            if (left..right).contains(&(column as usize)) {
+                return Some(i);
+            }
+            left = right + 1;
+        }
+        None
+    }
+
+    /// Rows containing highlighted text.
+    fn match_rows(&self) -> Vec<usize> {
+        let matcher = match self.state.highlight {
+            Some(ref m) => m,
+            None => return vec![],
+        };
+        self.visible
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| matcher.is_match(&self.row_item(**i).text()))
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Move the cursor to the next (or previous) row containing the find text,
+    /// wrapping around at the ends. Returns the 1-based match number and the count.
+    pub fn find_next(&mut self, forward: bool) -> Option<(usize, usize)> {
+        let rows = self.match_rows();
+        if rows.is_empty() {
+            return None;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let n = if forward {
+            rows.iter().position(|r| *r > current).unwrap_or(0)
+        } else {
+            rows.iter()
+                .rposition(|r| *r < current)
+                .unwrap_or(rows.len() - 1)
+        };
+        self.state.select(Some(rows[n]));
+        Some((n + 1, rows.len()))
+    }
+
+    pub fn min_level(&self) -> Option<Level> {
+        self.min_level
+    }
+
+    /// Raise the level rows have to be at least to be shown, wrapping around to show all rows.
+    pub fn cycle_min_level(&mut self) {
+        self.min_level = match self.min_level {
+            None | Some(Level::Trace) => Some(Level::Debug),
+            Some(Level::Debug) => Some(Level::Info),
+            Some(Level::Info) => Some(Level::Warn),
+            Some(Level::Warn) => Some(Level::Error),
+            Some(Level::Error) => None,
+        };
+        self.refilter();
+    }
+
+    pub fn timestamp_style(&self) -> &TimestampStyle {
+        &self.timestamp_style
+    }
+
+    /// Show timestamps of every row in `style`.
+    pub fn set_timestamp_style(&mut self, style: TimestampStyle) {
+        for item in self.items.iter_mut() {
+            item.show_time(&style);
+        }
+        self.timestamp_style = style;
+        self.refilter();
+    }
+
+    /// Show relative times again, as rows get older. Absolute times are left as they are.
+    pub fn refresh_times(&mut self) {
+        if !self.timestamp_style.relative {
+            return;
+        }
+        let style = &self.timestamp_style;
+        for item in self.items.iter_mut() {
+            item.show_time(style);
+        }
+        let summaries: Vec<(usize, LogListItem)> = self
+            .summaries
+            .keys()
+            .map(|first| (*first, self.summarize(&self.groups[first])))
+            .collect();
+        self.summaries.extend(summaries);
+    }
+
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    /// Show only rows of the template `pattern`, or rows of any template.
+    pub fn set_pattern(&mut self, pattern: Option<String>) {
+        self.pattern = pattern;
+        self.refilter();
+    }
+
+    fn is_visible(&self, item: &LogListItem) -> bool {
+        let level = item.level.unwrap_or(Level::Info);
+        self.min_level.map(|min| level >= min).unwrap_or(true)
+            && (!self.filtering || self.filter.matches(&item.text()))
+            && self
+                .pattern
+                .as_ref()
+                .map(|p| template_of(item.message()) == *p)
+                .unwrap_or(true)
+            && self
+                .side
+                .map(|side| item.side == Some(side))
+                .unwrap_or(true)
+    }
+
+    pub fn side(&self) -> Option<Side> {
+        self.side
+    }
+
+    /// Whether the rows are of a diff of two result sets.
+    pub fn is_diff(&self) -> bool {
+        self.items.iter().any(|item| item.side.is_some())
+    }
+
+    /// Show only rows of B, of A, of both, and all rows in turn.
+    pub fn cycle_side(&mut self) {
+        self.side = match self.side {
+            None => Some(Side::OnlyB),
+            Some(Side::OnlyB) => Some(Side::OnlyA),
+            Some(Side::OnlyA) => Some(Side::Both),
+            Some(Side::Both) => None,
+        };
+        self.refilter();
+    }
+
+    pub fn collapse(&self) -> Collapse {
+        self.collapse
+    }
+
+    pub fn cycle_collapse(&mut self) {
+        self.collapse = self.collapse.next();
+        self.expanded.clear();
+        self.refilter();
+    }
+
+    /// Expand the collapsed group under the cursor, or collapse the group back.
+    /// Returns false if the row is not part of a group.
+    pub fn toggle_expanded(&mut self) -> bool {
+        let first = match self.selected_index().and_then(|i| self.group_of.get(&i)) {
+            Some(first) => *first,
+            None => return false,
+        };
+        if !self.expanded.remove(&first) {
+            self.expanded.insert(first);
+        }
+        self.refilter();
+        if let Some(row) = self.visible.iter().position(|i| *i == first) {
+            self.state.select(Some(row));
+        }
+        true
+    }
+
+    /// Recompute `visible`, keeping the cursor on the same item or the nearest one after it.
+    fn refilter(&mut self) {
+        let selected = self.selected_index();
+        let indices = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| self.is_visible(item))
+            .map(|(i, _)| i)
+            .collect();
+        self.visible = vec![];
+        self.summaries.clear();
+        self.groups.clear();
+        self.group_of.clear();
+        for group in self.collapse.group(&self.items, indices) {
+            let first = group[0];
+            if group.len() == 1 {
+                self.visible.push(first);
+                continue;
+            }
+            if self.expanded.contains(&first) {
+                self.visible.extend(&group);
+            } else {
+                self.visible.push(first);
+                self.summaries.insert(first, self.summarize(&group));
+            }
+            for i in group.iter() {
+                self.group_of.insert(*i, first);
+            }
+            self.groups.insert(first, group);
+        }
+        let row = selected
+            .and_then(|index| self.visible.iter().position(|i| *i >= index))
+            .unwrap_or_else(|| self.visible.len().saturating_sub(1));
+        self.state.anchor = None;
+        self.state.offset = self.state.offset.min(row);
+        self.state.select(Some(row));
+    }
+
+    /// Row standing for the items of `group`, with their count and time span.
+    fn summarize(&self, group: &[usize]) -> LogListItem {
+        let first = &self.items[group[0]];
+        let items = group.iter().map(|i| &self.items[*i]);
+        let key = |item: &&LogListItem| (item.time, item.timestamp.clone());
+        let earliest = items.clone().min_by_key(key).unwrap_or(first);
+        let latest = items.max_by_key(key).unwrap_or(first);
+        LogListItem {
+            level: first.level,
+            style: first.style,
+            side: first.side,
+            ..LogListItem::new(
+                format!("{} - {}", earliest.shown_time, latest.shown_time),
+                format!("×{} {}", group.len(), first.log),
+            )
+        }
     }
 
-    pub fn push(&mut self, item: LogListItem) {
+    pub fn push(&mut self, mut item: LogListItem) {
+        item.show_time(&self.timestamp_style);
+        if self.is_visible(&item) {
+            self.visible.push(self.items.len());
+        }
         self.items.push(item);
     }
 
+    /// Add `items` at once, which is cheaper than pushing them one by one
+    /// while collapsing repeated messages.
+    pub fn extend(&mut self, items: impl IntoIterator<Item = LogListItem>) {
+        if self.collapse == Collapse::Off {
+            items.into_iter().for_each(|item| self.push(item));
+        } else {
+            let style = &self.timestamp_style;
+            self.items.extend(items.into_iter().map(|mut item| {
+                item.show_time(style);
+                item
+            }));
+            self.refilter();
+        }
+    }
+
     pub fn clear(&mut self) {
         self.items = vec![];
+        self.visible = vec![];
+        self.summaries.clear();
+        self.groups.clear();
+        self.group_of.clear();
+        self.expanded.clear();
         self.state.offset = 0;
         self.state.selected = Some(0);
+        self.state.anchor = None;
+    }
+
+    /// Item shown for `items[index]`, which is a summary if it starts a collapsed group.
+    pub fn row_item(&self, index: usize) -> &LogListItem {
+        self.summaries.get(&index).unwrap_or(&self.items[index])
+    }
+
+    /// Time of the item under the cursor, or of the first item of a collapsed group.
+    pub fn selected_time(&self) -> Option<DateTime<Utc>> {
+        self.selected_index().and_then(|i| self.items[i].time)
+    }
+
+    /// Select the shown row whose time is nearest to `time`.
+    pub fn select_nearest(&mut self, time: DateTime<Utc>) {
+        let items = &self.items;
+        let nearest = self
+            .visible
+            .iter()
+            .enumerate()
+            .filter_map(|(row, i)| items[*i].time.map(|t| (row, (t - time).num_milliseconds())))
+            .min_by_key(|(_, d)| d.abs())
+            .map(|(row, _)| row);
+        if nearest.is_some() {
+            self.state.select(nearest);
+        }
+    }
+
+    /// Earliest time of the loaded items.
+    pub fn oldest_time(&self) -> Option<DateTime<Utc>> {
+        self.items.iter().filter_map(|item| item.time).min()
+    }
+
+    /// `(@timestamp, @message)` of the loaded items in the second starting at `second`.
+    pub fn keys_at(&self, second: i64) -> HashSet<(String, String)> {
+        self.items
+            .iter()
+            .filter(|item| item.time.map(|t| t.timestamp()) == Some(second))
+            .map(|item| (item.timestamp.clone(), item.log.clone()))
+            .collect()
+    }
+
+    /// Index in `items` of the item under the cursor.
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|row| self.visible.get(row))
+            .copied()
+    }
+
+    pub fn is_selecting(&self) -> bool {
+        self.state.anchor.is_some()
+    }
+
+    /// Start a range selection at the cursor, or cancel the current one.
+    pub fn toggle_selecting(&mut self) {
+        self.state.anchor = match self.state.anchor {
+            Some(_) => None,
+            None => self.state.selected,
+        };
+    }
+
+    pub fn cancel_selecting(&mut self) {
+        self.state.anchor = None;
+    }
+
+    pub fn selected_items(&self) -> Vec<&LogListItem> {
+        match self.state.selected_range() {
+            Some(range) => range
+                .filter_map(|row| self.visible.get(row))
+                .flat_map(|i| match self.summaries.get(i) {
+                    Some(_) => self.groups[i].clone(),
+                    None => vec![*i],
+                })
+                .map(|i| &self.items[i])
+                .collect(),
+            None => vec![],
+        }
     }
 
     pub fn next_if_exist(&mut self) {
         if let Some(i) = self.state.selected() {
-            if i < self.items.len() - 1 {
+            if i + 1 < self.visible.len() {
                 self.state.select(Some(i + 1));
             }
         };
@@ -97,7 +597,7 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
     }
 
     pub fn next_page_if_exist(&mut self) {
-        if self.state.end_index < self.items.len() - 1 {
+        if self.state.end_index + 1 < self.visible.len() {
             self.state.select(Some(self.state.end_index));
             self.state.offset = self.state.end_index;
         }
@@ -128,97 +628,203 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
         self.state.prev_page_start_index = index;
     }
 
-    pub fn on_key(&mut self, key: KeyEvent) {
-        match key {
-            // down
-            KeyEvent {
-                code: KeyCode::Char('n'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Down,
-                modifiers: KeyModifiers::NONE,
-            } => self.next_if_exist(),
-            // up
-            KeyEvent {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::NONE,
-            } => self.previous_if_exist(),
-            // page up
-            KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::ALT,
-            }
-            | KeyEvent {
-                code: KeyCode::PageUp,
-                modifiers: KeyModifiers::NONE,
-            } => self.previous_page_if_exist(),
-            // page down
-            KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::CONTROL,
+    /// Row shown at line `line` of a list `width` columns wide.
+    pub fn row_at(&self, line: u16, width: u16) -> Option<usize> {
+        let width = if self.is_diff() {
+            width.saturating_sub(GUTTER_WIDTH)
+        } else {
+            width
+        };
+        let mut top = 0;
+        for (row, i) in self.visible.iter().enumerate().skip(self.state.offset) {
+            top += self.row_item(*i).height(width);
+            if (line as usize) < top {
+                return Some(row);
             }
-            | KeyEvent {
-                code: KeyCode::PageDown,
-                modifiers: KeyModifiers::NONE,
-            } => self.next_page_if_exist(),
+        }
+        None
+    }
+
+    pub fn select(&mut self, row: usize) {
+        if row < self.visible.len() {
+            self.state.select(Some(row));
+        }
+    }
+
+    pub fn first(&mut self) {
+        self.state.select(Some(0));
+    }
+
+    pub fn last(&mut self) {
+        self.state
+            .select(Some(self.visible.len().saturating_sub(1)));
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.next_if_exist(),
+            Action::Up => self.previous_if_exist(),
+            Action::PageUp => self.previous_page_if_exist(),
+            Action::PageDown => self.next_page_if_exist(),
+            Action::Top => self.first(),
+            Action::Bottom => self.last(),
+            Action::VisualSelect => self.toggle_selecting(),
             _ => {}
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PinnedTerm {
+    pub text: String,
+    re: Regex,
+}
+
+impl PinnedTerm {
+    /// Text shown in the legend of pinned terms.
+    pub fn label(&self) -> String {
+        format!(" {} ", self.text)
+    }
+}
+
 #[derive(Debug)]
 pub struct LogListItem {
     log: String,
+    /// `@timestamp` as returned by Insights.
     timestamp: String,
+    time: Option<DateTime<Utc>>,
+    /// Timestamp shown in the row.
+    shown_time: String,
+    fields: Vec<(String, String)>,
+    level: Option<Level>,
     style: Style,
+    /// Side of a diff the row is on.
+    side: Option<Side>,
     line_builder: LineBuilder,
 }
 
 impl LogListItem {
     pub fn new(timestamp: String, log: String) -> Self {
         LogListItem {
+            level: Level::detect(&log),
             log,
+            time: parse_timestamp(&timestamp),
+            shown_time: timestamp.clone(),
             timestamp,
+            fields: vec![],
             style: Style::default(),
+            side: None,
             line_builder: LineBuilder::new(),
         }
     }
 
-    // pub fn style(mut self, style: Style) -> Self {
-    //     self.style = style;
-    //     self
-    // }
+    /// Copy of this item for a diff, on `side`.
+    pub fn diffed(&self, side: Side) -> LogListItem {
+        LogListItem {
+            style: self.style,
+            side: Some(side),
+            ..LogListItem::new(self.timestamp.clone(), self.log.clone()).fields(self.fields.clone())
+        }
+    }
+
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.time
+    }
+
+    /// `@timestamp` as returned by Insights.
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// All fields of the result row, empty for rows made without them.
+    pub fn record(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    pub fn side(&self) -> Option<Side> {
+        self.side
+    }
+
+    pub fn diff_side(mut self, side: Option<Side>) -> Self {
+        self.side = side;
+        self
+    }
+
+    pub fn fields(mut self, fields: Vec<(String, String)>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.log
+    }
+
+    /// The whole result record as a JSON object.
+    pub fn to_json(&self) -> String {
+        let record: serde_json::Map<String, serde_json::Value> = if self.fields.is_empty() {
+            vec![
+                ("@timestamp".to_owned(), self.timestamp.clone().into()),
+                ("@message".to_owned(), self.log.clone().into()),
+            ]
+            .into_iter()
+            .collect()
+        } else {
+            self.fields
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone().into()))
+                .collect()
+        };
+        serde_json::Value::Object(record).to_string()
+    }
+
+    pub fn level(&self) -> Option<Level> {
+        self.level
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Show the timestamp in `style`, if it is a time.
+    fn show_time(&mut self, style: &TimestampStyle) {
+        if let Some(ref time) = self.time {
+            self.shown_time = style.format(time);
+        }
+    }
 
     pub fn text(&self) -> String {
-        format!("{}    {}", self.timestamp, self.log)
+        format!("{}    {}", self.shown_time, self.log)
     }
 
     pub fn height(&self, w: u16) -> usize {
-        self.line_builder.run_composer(&self.text(), w, "").len()
+        self.line_builder.run_composer(&self.text(), w, &[]).len()
     }
 }
 
 #[derive(Debug)]
 pub struct LogList<'a, D> {
     block: Option<Block<'a>>,
-    items: &'a [LogListItem],
+    items: Vec<&'a LogListItem>,
     style: Style,
     highlight_style: Style,
+    find_style: Style,
+    pinned_styles: Vec<Style>,
+    /// Styles of the gutter marking rows only in A, only in B and in both.
+    side_styles: [Style; 3],
     _phantom: std::marker::PhantomData<fn() -> D>,
 }
 
 impl<'a, D> LogList<'a, D> {
-    pub fn new(items: &'a [LogListItem]) -> LogList<'a, D> {
+    pub fn new(items: Vec<&'a LogListItem>) -> LogList<'a, D> {
         Self {
             block: None,
             style: Style::default(),
             items,
             highlight_style: Style::default(),
+            find_style: Style::default(),
+            pinned_styles: vec![Style::default()],
+            side_styles: [Style::default(); 3],
             _phantom: std::marker::PhantomData,
         }
     }
@@ -237,6 +843,22 @@ impl<'a, D> LogList<'a, D> {
         self.highlight_style = style;
         self
     }
+
+    pub fn find_style(mut self, style: Style) -> LogList<'a, D> {
+        self.find_style = style;
+        self
+    }
+
+    /// Styles of pinned terms, used in turn. Must not be empty.
+    pub fn pinned_styles(mut self, styles: Vec<Style>) -> LogList<'a, D> {
+        self.pinned_styles = styles;
+        self
+    }
+
+    pub fn side_styles(mut self, only_a: Style, only_b: Style, both: Style) -> LogList<'a, D> {
+        self.side_styles = [only_a, only_b, both];
+        self
+    }
 }
 
 impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
@@ -260,6 +882,17 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
         if self.items.is_empty() {
             return;
         }
+        // Rows of a diff start with a gutter marking their side.
+        let gutter_width = if self.items.iter().any(|item| item.side.is_some()) {
+            GUTTER_WIDTH
+        } else {
+            0
+        };
+        let text_width = list_area.width.saturating_sub(gutter_width);
+        // Too narrow to show any text next to the gutter.
+        if text_width == 0 {
+            return;
+        }
         let list_height = list_area.height as usize;
 
         let mut start = state.offset;
@@ -268,7 +901,7 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
         let mut height = 0;
 
         for item in self.items.iter().skip(state.offset) {
-            let item_height = item.height(list_area.width);
+            let item_height = item.height(text_width);
             if height + item_height > list_height {
                 if height != list_height {
                     let overflow = (height + item_height - list_height) as u16;
@@ -283,22 +916,34 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
 
         let selected = state.selected.unwrap_or(0).min(self.items.len() - 1);
         while selected >= end {
-            height = height.saturating_add(self.items[end].height(list_area.width));
+            height = height.saturating_add(self.items[end].height(text_width));
             end += 1;
             while height > list_height {
-                height = height.saturating_sub(self.items[start].height(list_area.width));
+                height = height.saturating_sub(self.items[start].height(text_width));
                 start += 1;
             }
         }
         while selected < start {
             start -= 1;
-            height = height.saturating_add(self.items[start].height(list_area.width));
+            height = height.saturating_add(self.items[start].height(text_width));
             while height > list_height {
                 end -= 1;
-                height = height.saturating_sub(self.items[end].height(list_area.width));
+                height = height.saturating_sub(self.items[end].height(text_width));
             }
         }
         state.offset = start;
+        let highlights: Vec<(&Regex, Style)> = state
+            .highlight
+            .iter()
+            .map(|re| (re, self.find_style))
+            .chain(
+                state
+                    .pinned
+                    .iter()
+                    .zip(self.pinned_styles.iter().cycle())
+                    .map(|(p, style)| (&p.re, *style)),
+            )
+            .collect();
         let mut current_height = 0;
         for (i, item) in self
             .items
@@ -307,7 +952,7 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
             .skip(state.offset)
             .take(end - start)
         {
-            let item_height = item.height(list_area.width) as u16;
+            let item_height = item.height(text_width) as u16;
             let (x, y) = {
                 let pos = (list_area.left(), list_area.top() + current_height);
                 current_height += item_height as u16;
@@ -333,17 +978,28 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
             let item_style = self.style.patch(item.style);
             buf.set_style(area, item_style);
 
-            let is_selected = state.selected.map(|s| s == i).unwrap_or(false);
-            let elem_x = x;
+            let is_selected = state
+                .selected_range()
+                .map(|r| r.contains(&i))
+                .unwrap_or(false);
+            let elem_x = x + gutter_width;
 
             if is_selected {
                 buf.set_style(area, self.highlight_style);
             }
+            if let Some(side) = item.side {
+                let (mark, style) = match side {
+                    Side::OnlyA => ("-", self.side_styles[0]),
+                    Side::OnlyB => ("+", self.side_styles[1]),
+                    Side::Both => ("=", self.side_styles[2]),
+                };
+                buf.set_string(x, y, mark, style);
+            }
 
-            let max_element_width = (list_area.width - (elem_x - x)) as usize;
+            let max_element_width = list_area.width.saturating_sub(elem_x - x) as usize;
             for (j, line) in item
                 .line_builder
-                .run_composer(&item.text(), list_area.width, &state.find_text)
+                .run_composer(&item.text(), text_width, &highlights)
                 .iter()
                 .enumerate()
             {
@@ -362,7 +1018,7 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
         if state.end_index != end {
             state
                 .dispatcher
-                .dispatch(crate::app::Message::UpdateLogListEndIndex(end));
+                .dispatch(crate::app::Message::UpdateLogListEndIndex(state.tab, end));
         }
 
         let mut prev_page_start_index = state.offset;
@@ -371,7 +1027,7 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
             if prev_page_start_index == 0 {
                 break;
             }
-            let item_height = self.items[prev_page_start_index].height(list_area.width);
+            let item_height = self.items[prev_page_start_index].height(text_width);
             height += item_height;
             if height > list_height {
                 break;
@@ -383,6 +1039,7 @@ impl<'a, D: Dispatcher<Message = Message>> StatefulWidget for LogList<'a, D> {
             state
                 .dispatcher
                 .dispatch(crate::app::Message::UpdateLogListPrevPageStartIndex(
+                    state.tab,
                     prev_page_start_index,
                 ));
         }
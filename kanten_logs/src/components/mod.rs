@@ -1,13 +1,19 @@
 pub mod block_component;
 pub mod checkbox;
+pub mod command_palette;
 pub mod group_list;
 pub mod inline_component;
 pub mod input;
 pub mod log_list;
+pub mod pattern_list;
+pub mod time_picker;
 
 pub use block_component::*;
 pub use checkbox::*;
+pub use command_palette::*;
 pub use group_list::*;
 pub use inline_component::*;
 pub use input::*;
 pub use log_list::*;
+pub use pattern_list::*;
+pub use time_picker::*;
@@ -0,0 +1,42 @@
+use tui::widgets::ListState;
+
+use crate::app::Action;
+use crate::models::template::Cluster;
+
+/// Templates of the loaded messages, most frequent first.
+pub struct PatternList {
+    pub clusters: Vec<Cluster>,
+    pub state: ListState,
+}
+
+impl PatternList {
+    pub fn new(clusters: Vec<Cluster>) -> Self {
+        let mut state = ListState::default();
+        if !clusters.is_empty() {
+            state.select(Some(0));
+        }
+        PatternList { clusters, state }
+    }
+
+    pub fn selected(&self) -> Option<&Cluster> {
+        self.state.selected().and_then(|i| self.clusters.get(i))
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        let last = self.clusters.len().saturating_sub(1);
+        let selected = match self.state.selected() {
+            Some(i) => i,
+            None => return,
+        };
+        let selected = match action {
+            Action::Down => (selected + 1).min(last),
+            Action::Up => selected.saturating_sub(1),
+            Action::PageDown => (selected + 10).min(last),
+            Action::PageUp => selected.saturating_sub(10),
+            Action::Top => 0,
+            Action::Bottom => last,
+            _ => return,
+        };
+        self.state.select(Some(selected));
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use tui::widgets::ListState;
+
+use crate::app::Action;
+use crate::models::{self, Zone};
+
+/// Ranges ending now offered by the picker.
+pub const PRESETS: &[&str] = &["5m", "15m", "1h", "3h", "12h", "24h", "7d"];
+
+const EXPRESSION_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+/// Part of the picker keys go to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PickerField {
+    Presets,
+    Start,
+    End,
+}
+
+/// Part of a date and time changed by up and down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimePart {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+}
+
+impl TimePart {
+    const ALL: [TimePart; 5] = [
+        TimePart::Year,
+        TimePart::Month,
+        TimePart::Day,
+        TimePart::Hour,
+        TimePart::Minute,
+    ];
+}
+
+/// Popup to pick the time range of the query, from a preset or by editing the start and end.
+pub struct TimePicker {
+    pub field: PickerField,
+    pub part: TimePart,
+    pub presets: ListState,
+    /// Start and end of a custom range, in `zone`.
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub zone: Zone,
+}
+
+impl TimePicker {
+    /// Picker showing the range of the duration input `value`.
+    pub fn new(value: &str, zone: Zone) -> Self {
+        let mut presets = ListState::default();
+        let preset = PRESETS.iter().position(|p| *p == value.trim());
+        presets.select(Some(preset.unwrap_or(1)));
+
+        let now = Utc::now().timestamp();
+        let (start, end) = match models::Duration::parse(value, &zone) {
+            models::Duration::Duration { start, end } => {
+                (start.unwrap_or(now - 15 * 60), end.unwrap_or(now))
+            }
+        };
+        let local = |t: i64| {
+            let t = zone.to_local(t);
+            t.with_second(0).unwrap_or(t)
+        };
+        TimePicker {
+            field: if preset.is_some() {
+                PickerField::Presets
+            } else {
+                PickerField::Start
+            },
+            part: TimePart::Hour,
+            presets,
+            start: local(start),
+            end: local(end),
+            zone,
+        }
+    }
+
+    /// Date and time being edited, if any.
+    pub fn editing(&self) -> Option<NaiveDateTime> {
+        match self.field {
+            PickerField::Presets => None,
+            PickerField::Start => Some(self.start),
+            PickerField::End => Some(self.end),
+        }
+    }
+
+    /// Canonical duration expression of the picked range.
+    pub fn expression(&self) -> String {
+        match self.field {
+            PickerField::Presets => self.preset().to_owned(),
+            _ => format!(
+                "{} - {}",
+                self.start.format(EXPRESSION_FORMAT),
+                self.end.format(EXPRESSION_FORMAT)
+            ),
+        }
+    }
+
+    /// The picked range in `zone`, or an error if it is empty.
+    pub fn range(&self) -> Result<(NaiveDateTime, NaiveDateTime), &'static str> {
+        let (start, end) = match self.field {
+            PickerField::Presets => {
+                let length = humantime::parse_duration(self.preset()).unwrap_or_default();
+                let end = self.zone.to_local(Utc::now().timestamp());
+                (end - Duration::seconds(length.as_secs() as i64), end)
+            }
+            _ => (self.start, self.end),
+        };
+        if start < end {
+            Ok((start, end))
+        } else {
+            Err("start is not before end")
+        }
+    }
+
+    fn preset(&self) -> &'static str {
+        PRESETS[self.presets.selected().unwrap_or(0)]
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::FocusNext => {
+                self.field = match self.field {
+                    PickerField::Presets => PickerField::Start,
+                    PickerField::Start => PickerField::End,
+                    PickerField::End => PickerField::Presets,
+                }
+            }
+            Action::FocusPrev => {
+                self.field = match self.field {
+                    PickerField::Presets => PickerField::End,
+                    PickerField::Start => PickerField::Presets,
+                    PickerField::End => PickerField::Start,
+                }
+            }
+            Action::CursorForward | Action::CursorBackward => {
+                let i = TimePart::ALL
+                    .iter()
+                    .position(|p| *p == self.part)
+                    .unwrap_or(0);
+                let i = if action == Action::CursorForward {
+                    (i + 1).min(TimePart::ALL.len() - 1)
+                } else {
+                    i.saturating_sub(1)
+                };
+                self.part = TimePart::ALL[i];
+            }
+            Action::Up => self.step(1),
+            Action::Down => self.step(-1),
+            Action::PageUp => self.step(10),
+            Action::PageDown => self.step(-10),
+            _ => {}
+        }
+    }
+
+    /// Move the preset selection, or change the part of the edited time by `n`.
+    fn step(&mut self, n: i64) {
+        let part = self.part;
+        let time = match self.field {
+            PickerField::Presets => {
+                let i = self.presets.selected().unwrap_or(0) as i64;
+                let i = (i - n).max(0).min(PRESETS.len() as i64 - 1);
+                self.presets.select(Some(i as usize));
+                return;
+            }
+            PickerField::Start => &mut self.start,
+            PickerField::End => &mut self.end,
+        };
+        *time = match part {
+            TimePart::Year => add_months(*time, n * 12),
+            TimePart::Month => add_months(*time, n),
+            TimePart::Day => *time + Duration::days(n),
+            TimePart::Hour => *time + Duration::hours(n),
+            TimePart::Minute => *time + Duration::minutes(n),
+        };
+    }
+}
+
+/// `time` moved by `n` months, keeping the day within the month.
+fn add_months(time: NaiveDateTime, n: i64) -> NaiveDateTime {
+    let months = time.year() as i64 * 12 + time.month0() as i64 + n;
+    let (year, month) = ((months / 12) as i32, (months % 12) as u32 + 1);
+    let day = time.day().min(days_in_month(year, month));
+    NaiveDate::from_ymd_opt(year, month, day)
+        .map(|d| d.and_time(time.time()))
+        .unwrap_or(time)
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
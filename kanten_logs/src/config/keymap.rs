@@ -0,0 +1,465 @@
+use std::{collections::HashMap, convert::TryFrom, fmt};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer};
+
+use crate::app::Action;
+
+/// A single key with modifiers, written like `ctrl-n`, `alt-v`, `pagedown` or `G`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        if self.code != key.code {
+            return false;
+        }
+        // Terminals disagree on whether SHIFT is reported with upper case chars.
+        if let KeyCode::Char(_) = key.code {
+            return (self.modifiers - KeyModifiers::SHIFT) == (key.modifiers - KeyModifiers::SHIFT);
+        }
+        self.modifiers == key.modifiers
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::str::FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // `-` itself is a valid key, so only split while a modifier prefix remains.
+        loop {
+            let lower = rest.to_lowercase();
+            if lower.starts_with("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = &rest[5..];
+            } else if lower.starts_with("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = &rest[4..];
+            } else if lower.starts_with("shift-") {
+                modifiers |= KeyModifiers::SHIFT;
+                rest = &rest[6..];
+            } else {
+                break;
+            }
+        }
+        let code = match rest.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            f if f.len() > 1 && f.starts_with('f') => {
+                let n = f[1..]
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid key {:?}", s))?;
+                KeyCode::F(n)
+            }
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => {
+                        if c.is_uppercase() {
+                            modifiers |= KeyModifiers::SHIFT;
+                        }
+                        KeyCode::Char(c)
+                    }
+                    _ => return Err(format!("invalid key {:?}", s)),
+                }
+            }
+        };
+        Ok(KeyBinding { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        // Upper case chars already imply SHIFT.
+        if self.modifiers.contains(KeyModifiers::SHIFT) && !matches!(self.code, KeyCode::Char(_)) {
+            write!(f, "shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// Keys pressed one after another, written like `g g`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeySequence(pub Vec<KeyBinding>);
+
+impl KeySequence {
+    fn starts_with(&self, keys: &[KeyEvent]) -> bool {
+        self.0.len() >= keys.len() && self.0.iter().zip(keys).all(|(b, k)| b.matches(k))
+    }
+}
+
+impl TryFrom<String> for KeySequence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let keys = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<KeyBinding>, _>>()?;
+        if keys.is_empty() {
+            return Err("empty key sequence".to_owned());
+        }
+        Ok(KeySequence(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(|k| k.to_string()).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// Where a binding is active. Bindings of the focused context win over `Global`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Global,
+    Input,
+    Groups,
+    Logs,
+    Palette,
+    Patterns,
+    Picker,
+}
+
+impl KeyContext {
+    /// Contexts searched for a binding, most specific first.
+    pub fn chain(&self) -> &'static [KeyContext] {
+        match self {
+            KeyContext::Global => &[KeyContext::Global],
+            KeyContext::Input => &[KeyContext::Input, KeyContext::Global],
+            KeyContext::Groups => &[KeyContext::Groups, KeyContext::Global],
+            KeyContext::Logs => &[KeyContext::Logs, KeyContext::Global],
+            KeyContext::Palette => &[KeyContext::Palette, KeyContext::Input, KeyContext::Global],
+            KeyContext::Patterns => &[KeyContext::Patterns, KeyContext::Global],
+            KeyContext::Picker => &[KeyContext::Picker, KeyContext::Global],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    Default,
+    Emacs,
+    Vim,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Preset::Default
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Resolved {
+    Action(Action),
+    /// The keys so far are a prefix of a longer sequence.
+    Pending,
+    Unbound,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyContext, Vec<(KeySequence, Action)>>,
+}
+
+impl Keymap {
+    pub fn preset(preset: Preset) -> Self {
+        use KeyCode::*;
+
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        let alt = KeyModifiers::ALT;
+        let shift = KeyModifiers::SHIFT;
+        let seq = |keys: &[(KeyCode, KeyModifiers)]| {
+            KeySequence(keys.iter().map(|(c, m)| KeyBinding::new(*c, *m)).collect())
+        };
+
+        let global = vec![
+            (seq(&[(Esc, none)]), Action::Quit),
+            (seq(&[(F(1), none)]), Action::ToggleHelp),
+            (seq(&[(Char('p'), ctrl)]), Action::CommandPalette),
+            (seq(&[(Char('g'), ctrl)]), Action::ToggleFilter),
+            (seq(&[(F(3), none)]), Action::FindNext),
+            (seq(&[(F(3), shift)]), Action::FindPrev),
+            (seq(&[(Char('r'), alt)]), Action::ToggleRegex),
+            (seq(&[(Char('c'), alt)]), Action::ToggleCaseSensitive),
+            (seq(&[(Char('w'), alt)]), Action::ToggleWholeWord),
+            (seq(&[(Char('p'), alt)]), Action::PinHighlight),
+            (seq(&[(Char('u'), alt)]), Action::UnpinHighlight),
+            (seq(&[(Char('l'), alt)]), Action::CycleMinLevel),
+            (seq(&[(Char('d'), alt)]), Action::CycleCollapse),
+            (seq(&[(Char('t'), alt)]), Action::ShowPatterns),
+            (seq(&[(Char('z'), alt)]), Action::CycleTimeZone),
+            (seq(&[(Char('t'), ctrl)]), Action::TimePicker),
+            (seq(&[(Char('c'), ctrl)]), Action::CancelQuery),
+            (seq(&[(Char('n'), alt)]), Action::NewTab),
+            (seq(&[(Char('q'), alt)]), Action::CloseTab),
+            (seq(&[(Right, alt)]), Action::NextTab),
+            (seq(&[(Left, alt)]), Action::PrevTab),
+            (seq(&[(Char('s'), alt)]), Action::ToggleSplit),
+            (seq(&[(Char('o'), alt)]), Action::SwitchPane),
+            (seq(&[(Char('y'), alt)]), Action::ToggleSyncScroll),
+            (seq(&[(Char('m'), alt)]), Action::DiffTabs),
+            (seq(&[(Char('r'), ctrl)]), Action::RestoreSession),
+            (seq(&[(Char('k'), alt)]), Action::CopyConsoleLink),
+            (seq(&[(Tab, none)]), Action::FocusNext),
+            (seq(&[(Tab, ctrl)]), Action::FocusPrev),
+            (seq(&[(Enter, none)]), Action::Submit),
+        ];
+        let input = vec![
+            (seq(&[(Char('a'), ctrl)]), Action::CursorStart),
+            (seq(&[(Home, none)]), Action::CursorStart),
+            (seq(&[(Char('e'), ctrl)]), Action::CursorEnd),
+            (seq(&[(End, none)]), Action::CursorEnd),
+            (seq(&[(Char('f'), ctrl)]), Action::CursorForward),
+            (seq(&[(Right, none)]), Action::CursorForward),
+            (seq(&[(Char('b'), ctrl)]), Action::CursorBackward),
+            (seq(&[(Left, none)]), Action::CursorBackward),
+            (seq(&[(Char('d'), ctrl)]), Action::DeleteForward),
+            (seq(&[(Delete, none)]), Action::DeleteForward),
+            (seq(&[(Char('h'), ctrl)]), Action::DeleteBackward),
+            (seq(&[(Backspace, none)]), Action::DeleteBackward),
+        ];
+        let mut list = vec![
+            (seq(&[(Char('n'), ctrl)]), Action::Down),
+            (seq(&[(Down, none)]), Action::Down),
+            (seq(&[(Char('p'), ctrl)]), Action::Up),
+            (seq(&[(Up, none)]), Action::Up),
+            (seq(&[(Char('v'), ctrl)]), Action::PageDown),
+            (seq(&[(PageDown, none)]), Action::PageDown),
+            (seq(&[(Char('v'), alt)]), Action::PageUp),
+            (seq(&[(PageUp, none)]), Action::PageUp),
+            (seq(&[(Home, none)]), Action::Top),
+            (seq(&[(End, none)]), Action::Bottom),
+            (seq(&[(Char('?'), none)]), Action::ToggleHelp),
+            (seq(&[(Char(':'), none)]), Action::CommandPalette),
+        ];
+        let palette = vec![
+            (seq(&[(Char('n'), ctrl)]), Action::Down),
+            (seq(&[(Down, none)]), Action::Down),
+            (seq(&[(Char('p'), ctrl)]), Action::Up),
+            (seq(&[(Up, none)]), Action::Up),
+        ];
+
+        match preset {
+            Preset::Default => {}
+            Preset::Emacs => {
+                list.push((seq(&[(Char('<'), alt)]), Action::Top));
+                list.push((seq(&[(Char('>'), alt)]), Action::Bottom));
+            }
+            Preset::Vim => {
+                list.push((seq(&[(Char('j'), none)]), Action::Down));
+                list.push((seq(&[(Char('k'), none)]), Action::Up));
+                list.push((seq(&[(Char('f'), ctrl)]), Action::PageDown));
+                list.push((seq(&[(Char('d'), ctrl)]), Action::PageDown));
+                list.push((seq(&[(Char('b'), ctrl)]), Action::PageUp));
+                list.push((seq(&[(Char('u'), ctrl)]), Action::PageUp));
+                list.push((seq(&[(Char('g'), none), (Char('g'), none)]), Action::Top));
+                list.push((seq(&[(Char('G'), shift)]), Action::Bottom));
+            }
+        }
+
+        let mut patterns = list.clone();
+        patterns.retain(|(_, a)| !matches!(a, Action::ToggleHelp | Action::CommandPalette));
+
+        let mut picker = patterns.clone();
+        picker.extend(vec![
+            (seq(&[(Right, none)]), Action::CursorForward),
+            (seq(&[(Left, none)]), Action::CursorBackward),
+        ]);
+        if preset == Preset::Vim {
+            picker.push((seq(&[(Char('l'), none)]), Action::CursorForward));
+            picker.push((seq(&[(Char('h'), none)]), Action::CursorBackward));
+        }
+
+        let mut bindings = HashMap::new();
+        bindings.insert(KeyContext::Global, global);
+        bindings.insert(KeyContext::Input, input);
+        let mut logs = list.clone();
+        logs.extend(vec![
+            (seq(&[(Char('v'), none)]), Action::VisualSelect),
+            (seq(&[(Char('y'), none)]), Action::Yank),
+            (seq(&[(Char('Y'), shift)]), Action::YankJson),
+            (seq(&[(Char('n'), none)]), Action::FindNext),
+            (seq(&[(Char('N'), shift)]), Action::FindPrev),
+            (seq(&[(Char('['), none)]), Action::ShiftEarlier),
+            (seq(&[(Char(']'), none)]), Action::ShiftLater),
+            (seq(&[(Char('+'), none)]), Action::ZoomIn),
+            (seq(&[(Char('-'), none)]), Action::ZoomOut),
+            (seq(&[(Char('z'), none)]), Action::AroundSelected),
+            (seq(&[(Char('o'), none)]), Action::LoadOlder),
+            (seq(&[(Char('O'), shift)]), Action::LoadAll),
+            (seq(&[(Char('='), none)]), Action::CycleDiffSide),
+        ]);
+        bindings.insert(KeyContext::Groups, list);
+        bindings.insert(KeyContext::Logs, logs);
+        bindings.insert(KeyContext::Palette, palette);
+        bindings.insert(KeyContext::Patterns, patterns);
+        bindings.insert(KeyContext::Picker, picker);
+        Keymap { bindings }
+    }
+
+    /// Replace the bindings of `action` in `context` with `keys`.
+    pub fn bind(&mut self, context: KeyContext, action: Action, keys: Vec<KeySequence>) {
+        let bindings = self.bindings.entry(context).or_default();
+        bindings.retain(|(_, a)| *a != action);
+        bindings.extend(keys.into_iter().map(|k| (k, action)));
+    }
+
+    /// Key sequences bound to `action` in `context`.
+    pub fn keys_for(&self, context: KeyContext, action: Action) -> Vec<&KeySequence> {
+        self.bindings
+            .get(&context)
+            .into_iter()
+            .flatten()
+            .filter(|(_, a)| *a == action)
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    /// Key sequences reaching `action` from `context`, including ones of outer
+    /// contexts not shadowed by inner ones.
+    pub fn effective_keys(&self, context: KeyContext, action: Action) -> Vec<&KeySequence> {
+        let mut keys = vec![];
+        let chain = context.chain();
+        for (i, c) in chain.iter().enumerate() {
+            for k in self.keys_for(*c, action) {
+                let shadowed = chain[..i]
+                    .iter()
+                    .flat_map(|c| self.bindings.get(c).into_iter().flatten())
+                    .any(|(seq, _)| seq == k);
+                if !shadowed {
+                    keys.push(k);
+                }
+            }
+        }
+        keys
+    }
+
+    /// Resolve keys pressed so far in `context`, falling back to outer contexts.
+    pub fn resolve(&self, context: KeyContext, keys: &[KeyEvent]) -> Resolved {
+        let mut pending = false;
+        for context in context.chain() {
+            for (seq, action) in self.bindings.get(context).into_iter().flatten() {
+                if !seq.starts_with(keys) {
+                    continue;
+                }
+                if seq.0.len() == keys.len() {
+                    return Resolved::Action(*action);
+                }
+                pending = true;
+            }
+        }
+        if pending {
+            Resolved::Pending
+        } else {
+            Resolved::Unbound
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::preset(Preset::default())
+    }
+}
+
+/// `[keymap]` section of the config file.
+/// Each table maps an action name to the key sequences bound to it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeymapConfig {
+    preset: Preset,
+    global: HashMap<Action, Vec<KeySequence>>,
+    input: HashMap<Action, Vec<KeySequence>>,
+    groups: HashMap<Action, Vec<KeySequence>>,
+    logs: HashMap<Action, Vec<KeySequence>>,
+    palette: HashMap<Action, Vec<KeySequence>>,
+    patterns: HashMap<Action, Vec<KeySequence>>,
+    picker: HashMap<Action, Vec<KeySequence>>,
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let config = KeymapConfig::deserialize(deserializer)?;
+        let mut keymap = Keymap::preset(config.preset);
+        for (context, table) in vec![
+            (KeyContext::Global, config.global),
+            (KeyContext::Input, config.input),
+            (KeyContext::Groups, config.groups),
+            (KeyContext::Logs, config.logs),
+            (KeyContext::Palette, config.palette),
+            (KeyContext::Patterns, config.patterns),
+            (KeyContext::Picker, config.picker),
+        ] {
+            for (action, keys) in table {
+                keymap.bind(context, action, keys);
+            }
+        }
+        Ok(keymap)
+    }
+}
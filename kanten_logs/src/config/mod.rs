@@ -0,0 +1,98 @@
+mod keymap;
+mod theme;
+
+pub use keymap::*;
+pub use theme::*;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::client::DEFAULT_LIMIT;
+use crate::models::{Zone, DEFAULT_TIME_FORMAT};
+
+pub const DEFAULT_SINCE: &str = "15m";
+pub const DEFAULT_AROUND_MINUTES: i64 = 5;
+/// Logs Insights price of the us-east-1 region, in USD.
+pub const DEFAULT_PRICE_PER_GB: f64 = 0.005;
+pub const DEFAULT_COST_WARNING: f64 = 1.0;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Default start of the time range, e.g. `15m` or `3h`.
+    pub since: Option<String>,
+    /// Default log group name regular expression.
+    pub group_name: Option<String>,
+    /// AWS profile name used when `--profile` is not specified.
+    pub profile: Option<String>,
+    /// AWS region used when `--region` is not specified.
+    pub region: Option<String>,
+    /// Max number of rows returned by a query.
+    pub limit: i32,
+    /// Time zone timestamps are shown and the duration is typed in:
+    /// `utc`, `local` or an IANA name like `Asia/Tokyo`.
+    pub timezone: Zone,
+    /// `strftime` like format of timestamps.
+    pub time_format: String,
+    /// Minutes before and after the selected line queried by `around_selected`.
+    pub around_minutes: i64,
+    /// Price of scanning a GB, used to estimate the cost of queries.
+    pub price_per_gb: f64,
+    /// Estimated cost over which a query has to be run twice to start.
+    pub cost_warning: f64,
+    /// Seconds after which a running query is stopped, keeping the rows found so far.
+    pub query_timeout: Option<u64>,
+    pub theme: Theme,
+    pub keymap: Keymap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            since: None,
+            group_name: None,
+            profile: None,
+            region: None,
+            limit: DEFAULT_LIMIT,
+            timezone: Zone::default(),
+            time_format: DEFAULT_TIME_FORMAT.to_owned(),
+            around_minutes: DEFAULT_AROUND_MINUTES,
+            price_per_gb: DEFAULT_PRICE_PER_GB,
+            cost_warning: DEFAULT_COST_WARNING,
+            query_timeout: None,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load config from `path`, or from the default location if `path` is `None`.
+    /// A missing file at the default location is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let path = default_config_path()?;
+                if !path.exists() {
+                    return Ok(Config::default());
+                }
+                path
+            }
+        };
+        let s = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config file {:?}.", path))?;
+        toml::from_str(&s).with_context(|| format!("failed to parse config file {:?}.", path))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/kanten/config.toml` or the OS equivalent.
+pub fn default_config_path() -> Result<PathBuf> {
+    let mut path =
+        dirs_next::config_dir().ok_or_else(|| anyhow!("failed to find os config dir."))?;
+    path.push("kanten");
+    path.push("config.toml");
+    Ok(path)
+}
@@ -0,0 +1,138 @@
+use serde::{de, Deserialize, Deserializer};
+use tui::style::Color;
+
+use crate::models::Level;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Background of the selected row.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub selection_bg: Color,
+    /// Background of the status bar.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub status_bg: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub focused_border: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub checkbox: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub find_fg: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub find_bg: Color,
+    /// Colors of rows by level. Trace rows use `level_debug`.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub level_error: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub level_warn: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub level_info: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub level_debug: Color,
+    /// Gutters of diff rows only in the first tab and only in the second.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub diff_only_a: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub diff_only_b: Color,
+    /// Backgrounds of pinned highlights, used in turn.
+    #[serde(deserialize_with = "deserialize_colors")]
+    pub pinned: Vec<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            selection_bg: Color::Rgb(72, 68, 96),
+            status_bg: Color::Rgb(72, 68, 96),
+            border: Color::DarkGray,
+            focused_border: Color::White,
+            checkbox: Color::Rgb(43, 116, 100),
+            find_fg: Color::Black,
+            find_bg: Color::Rgb(238, 173, 15),
+            level_error: Color::Rgb(224, 108, 117),
+            level_warn: Color::Rgb(229, 192, 123),
+            level_info: Color::Reset,
+            level_debug: Color::DarkGray,
+            diff_only_a: Color::Rgb(224, 108, 117),
+            diff_only_b: Color::Rgb(152, 195, 121),
+            pinned: vec![
+                Color::Rgb(86, 182, 194),
+                Color::Rgb(198, 120, 221),
+                Color::Rgb(152, 195, 121),
+                Color::Rgb(224, 108, 117),
+                Color::Rgb(97, 175, 239),
+            ],
+        }
+    }
+}
+
+impl Theme {
+    pub fn level_color(&self, level: Level) -> Color {
+        match level {
+            Level::Error => self.level_error,
+            Level::Warn => self.level_warn,
+            Level::Info => self.level_info,
+            Level::Debug | Level::Trace => self.level_debug,
+        }
+    }
+}
+
+/// Parse a color name like `darkgray` or a hex color like `#484460`.
+pub fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let v = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb((v >> 16) as u8, (v >> 8) as u8, v as u8));
+    }
+    let color = match s
+        .to_lowercase()
+        .replace(|c| c == '_' || c == '-', "")
+        .as_str()
+    {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_color(&s).ok_or_else(|| de::Error::custom(format!("invalid color {:?}", s)))
+}
+
+fn deserialize_colors<'de, D>(deserializer: D) -> Result<Vec<Color>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let colors = Vec::<String>::deserialize(deserializer)?;
+    if colors.is_empty() {
+        return Err(de::Error::custom("at least one color is required"));
+    }
+    colors
+        .iter()
+        .map(|s| parse_color(s).ok_or_else(|| de::Error::custom(format!("invalid color {:?}", s))))
+        .collect()
+}
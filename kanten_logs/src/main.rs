@@ -3,28 +3,30 @@ use async_trait::async_trait;
 #[allow(dead_code)]
 mod app;
 mod client;
+mod clipboard;
 mod components;
+mod config;
 mod models;
 mod option;
+mod session;
 
 use std::sync::mpsc::Sender;
 // use cloudwatchlogs::{Config, Credentials, Region};
 // https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_StartQuery.html
 use crate::{
     app::{view, App, Dispatcher, Message},
-    client::{GroupsClient, QueryClient},
+    client::{GetGroupsOutput, GroupsClient, QueryClient},
 };
 use client::{Client, SearchResult};
 
 use crossterm::{
-    event::{
-        poll, read, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyEvent,
-    },
+    event::{poll, read, DisableMouseCapture, EnableMouseCapture, Event as CEvent},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use simplelog::{Config, LevelFilter, WriteLogger};
 use std::{
+    collections::HashMap,
     error::Error,
     io::stdout,
     sync::mpsc,
@@ -37,6 +39,9 @@ use anyhow::{anyhow, Result};
 use structopt::StructOpt;
 use tui::{backend::CrosstermBackend, Terminal};
 
+/// How long to wait on exit for the ids of queries still starting, to stop them.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
 fn setup_logging() -> Result<()> {
     let mut path = get_app_cache_path()?;
     path.push("kanten.log");
@@ -86,25 +91,33 @@ impl AsyncTask for Service {
         match message {
             Message::GetQueryResultsRequest(query_id) => {
                 log::trace!("request query result");
-                if let Ok(SearchResult::Complete(items)) =
-                    self.client.get_default_query_results(&query_id).await
-                {
-                    log::trace!("items {}", items.len());
-                    Some(Message::GetQueryResultsComplete(items))
-                } else {
-                    // TODO: handle error
-                    std::thread::sleep(Duration::from_millis(100));
-                    self.run(Message::GetQueryResultsRequest(query_id)).await
+                match self.client.get_default_query_results(&query_id).await {
+                    Ok(SearchResult::Complete(items, statistics)) => {
+                        log::trace!("items {}", items.len());
+                        Some(Message::GetQueryResultsComplete(
+                            query_id, items, statistics,
+                        ))
+                    }
+                    // The app polls again, so that it can show the progress.
+                    Ok(SearchResult::Running(query_id, items, statistics)) => {
+                        std::thread::sleep(Duration::from_millis(100));
+                        Some(Message::GetQueryResultsRunning(query_id, items, statistics))
+                    }
+                    Err(_) => {
+                        // TODO: handle error
+                        std::thread::sleep(Duration::from_millis(100));
+                        self.run(Message::GetQueryResultsRequest(query_id)).await
+                    }
                 }
             }
-            Message::StartQueryRequest(input) => {
+            Message::StartQueryRequest(tab, input) => {
                 log::debug!("start query");
                 let query_id = self
                     .client
                     .start_default_query(input.clone())
                     .await
                     .unwrap_or_else(|_| panic!("Failed to start query {:?}", input));
-                Some(Message::StartQueryComplete(query_id))
+                Some(Message::StartQueryComplete(tab, query_id))
             }
             Message::StopQueryRequest(query_id) => {
                 let _ = self.client.stop_query(&query_id).await;
@@ -119,11 +132,46 @@ impl AsyncTask for Service {
 async fn main() -> Result<(), Box<dyn Error>> {
     setup_logging()?;
 
-    let opt = option::Opt::from_args();
+    let mut opt = option::Opt::from_args();
+    if let Some(ref url) = opt.from_url {
+        let link = models::ConsoleLink::parse(url)?;
+        opt = opt.prefill(&link);
+    }
+    let config = config::Config::load(opt.config.as_deref())?;
+    let opt = opt.merge(&config);
+
+    // Let the default provider chain pick these up, so that the usual
+    // AWS_PROFILE / AWS_REGION environment variables keep working as before.
+    if let Some(ref profile) = opt.profile {
+        std::env::set_var("AWS_PROFILE", profile);
+    }
+    if let Some(ref region) = opt.region {
+        std::env::set_var("AWS_REGION", region);
+    }
 
     let shared_config = aws_config::load_from_env().await;
-    let client = Client::new(cloudwatchlogs::Client::new(&shared_config));
-    let group_names = client.get_group_names().await?;
+    // The region resolved from the environment or the profile, for links to the console.
+    let region = shared_config.region().map(|region| region.to_string());
+    let client = Client::new(cloudwatchlogs::Client::new(&shared_config)).limit(config.limit);
+    let session = match opt.resume {
+        Some(Some(ref path)) => Some(session::Session::load(path)?),
+        Some(None) => Some(session::Session::load(&session::default_session_path()?)?),
+        None => None,
+    };
+    // A session can be read without access to AWS, listing only its groups.
+    let group_names = match (client.get_group_names().await, &session) {
+        (Ok(group_names), _) => group_names,
+        (Err(e), Some(session)) => {
+            log::error!("failed to get log groups {:?}", e);
+            GetGroupsOutput {
+                items: session.groups(),
+                sizes: HashMap::new(),
+            }
+        }
+        (Err(e), None) => return Err(e.into()),
+    };
+    let session_path = session::default_session_path()?;
+    let saved = session.is_none() && session_path.exists();
 
     let (tx0, rx0) = mpsc::channel::<Message>();
     let (tx1, rx1) = mpsc::channel::<Message>();
@@ -149,8 +197,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or_else(|| Duration::from_secs(0));
             if poll(timeout).unwrap() {
-                if let CEvent::Key(key) = read().unwrap() {
-                    tx2.send(Message::KeyInput(key)).unwrap();
+                match read().unwrap() {
+                    CEvent::Key(key) => tx2.send(Message::KeyInput(key)).unwrap(),
+                    CEvent::Mouse(mouse) => tx2.send(Message::MouseInput(mouse)).unwrap(),
+                    CEvent::Resize(..) => {}
                 }
             }
             if last_tick.elapsed() >= tick_rate {
@@ -177,28 +227,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     });
 
-    let mut app = App::new(messenger, group_names.items, opt);
+    let mut app = App::new(messenger, group_names, opt, config, region);
+    match session {
+        Some(session) => app.restore(session),
+        None if saved => app.offer_restore(),
+        None => {}
+    }
     terminal.clear()?;
 
     loop {
         terminal.draw(|f| view::draw(f, &mut app))?;
         let message = rx0.recv()?;
         match message {
-            Message::KeyInput(key) => match key {
-                KeyEvent {
-                    code: KeyCode::Esc, ..
-                } => {
-                    disable_raw_mode()?;
-                    execute!(
-                        terminal.backend_mut(),
-                        LeaveAlternateScreen,
-                        DisableMouseCapture
-                    )?;
-                    terminal.show_cursor()?;
-                    break;
-                }
-                _ => app.on_key(key)?,
-            },
+            Message::KeyInput(key) => app.on_key(key)?,
+            Message::MouseInput(mouse) => app.on_mouse(mouse),
             // TODO: remove await
             _ => app.update(message).await,
         }
@@ -206,5 +248,39 @@ async fn main() -> Result<(), Box<dyn Error>> {
             break;
         }
     }
+
+    // Stop the queries left running, which are charged until they complete.
+    for query_id in app.query_ids() {
+        let _ = client.stop_query(&query_id).await;
+    }
+    // Queries still starting, also of closed tabs, are stopped once their id arrives.
+    let mut starting = app.starting_queries();
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while starting > 0 {
+        match rx0.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Message::StartQueryComplete(_, query_id)) => {
+                let _ = client.stop_query(&query_id).await;
+                starting -= 1;
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    // A run without results keeps the last session, so that it can still be restored.
+    let session = app.session();
+    if session.has_results() {
+        if let Err(e) = session.save(&session_path) {
+            log::error!("failed to save session {:?}", e);
+        }
+    }
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
     Ok(())
 }
@@ -0,0 +1,416 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Time range of a Logs Insights link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkRange {
+    /// Unix timestamps.
+    Absolute { start: i64, end: i64 },
+    /// Seconds before now.
+    Relative { start: i64, end: i64 },
+}
+
+impl LinkRange {
+    /// Start as the `--since` option takes it.
+    pub fn since(&self) -> String {
+        match *self {
+            LinkRange::Absolute { start, .. } => rfc3339(start),
+            LinkRange::Relative { start, .. } => format!("{}s", start),
+        }
+    }
+
+    /// End as the `--end` option takes it.
+    pub fn end(&self) -> String {
+        match *self {
+            LinkRange::Absolute { end, .. } => rfc3339(end),
+            LinkRange::Relative { end: 0, .. } => "now".to_owned(),
+            LinkRange::Relative { end, .. } => format!("{}s", end),
+        }
+    }
+}
+
+fn time(t: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(t, 0).single()
+}
+
+fn rfc3339(t: i64) -> String {
+    time(t).map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+/// Logs Insights query opened in the AWS console by a link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleLink {
+    pub region: Option<String>,
+    pub query: String,
+    pub groups: Vec<String>,
+    pub range: LinkRange,
+}
+
+impl ConsoleLink {
+    /// URL opening the query in Logs Insights, like
+    /// `https://us-east-1.console.aws.amazon.com/cloudwatch/home?region=us-east-1#logsV2:logs-insights$3FqueryDetail$3D~(end~'...)`.
+    pub fn url(&self) -> String {
+        let range = match self.range {
+            LinkRange::Absolute { start, end } => {
+                let format = |t: i64| {
+                    time(t)
+                        .map(|t| t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+                        .unwrap_or_default()
+                };
+                format!(
+                    "end~'{}~start~'{}~timeType~'ABSOLUTE~tz~'UTC",
+                    escape(&format(end)),
+                    escape(&format(start))
+                )
+            }
+            LinkRange::Relative { start, end } => format!(
+                "end~{}~start~{}~timeType~'RELATIVE~unit~'seconds",
+                -end, -start
+            ),
+        };
+        let sources: String = self
+            .groups
+            .iter()
+            .map(|g| format!("~'{}", escape(g)))
+            .collect();
+        // The detail is made of characters encodeURIComponent keeps, so only
+        // the `?` and `=` around it are escaped, with `$` in place of `%`.
+        let detail = format!(
+            "~({}~editorString~'{}~isLiveTail~false~source~({}))",
+            range,
+            escape(&self.query),
+            sources
+        );
+        let (host, region) = match self.region {
+            Some(ref region) => (
+                format!("{}.console.aws.amazon.com", region),
+                format!("?region={}", region),
+            ),
+            None => ("console.aws.amazon.com".to_owned(), String::new()),
+        };
+        format!(
+            "https://{}/cloudwatch/home{}#logsV2:logs-insights$3FqueryDetail$3D{}",
+            host, region, detail
+        )
+    }
+
+    /// Parse a link to a Logs Insights query, as copied from the address bar.
+    pub fn parse(url: &str) -> Result<Self> {
+        let (base, fragment) = url
+            .split_once('#')
+            .ok_or_else(|| anyhow!("not a Logs Insights link: no fragment."))?;
+        // Links copied from the address bar are encoded again, like `$257E$2528end`.
+        let mut fragment = fragment.to_owned();
+        loop {
+            let decoded = unescape(&unescape(&fragment, '$'), '%');
+            if decoded == fragment {
+                break;
+            }
+            fragment = decoded;
+        }
+        let detail = fragment
+            .split_once("queryDetail=")
+            .map(|(_, detail)| detail.trim_start_matches('~'))
+            .ok_or_else(|| anyhow!("not a Logs Insights link: no queryDetail."))?;
+        let fields = match Parser::new(detail).value()? {
+            Value::Object(fields) => fields,
+            _ => return Err(anyhow!("queryDetail of the link is not an object.")),
+        };
+        let field = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let text = |key: &str| match field(key) {
+            Some(Value::Str(s)) => Some(s.as_str()),
+            _ => None,
+        };
+
+        let query = text("editorString").unwrap_or_default().to_owned();
+        let groups = match field("source") {
+            Some(Value::List(items)) => items
+                .iter()
+                .filter_map(|v| match v {
+                    Value::Str(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        let range = match text("timeType") {
+            Some("RELATIVE") => {
+                let unit = match text("unit") {
+                    Some("minutes") => 60,
+                    Some("hours") => 60 * 60,
+                    Some("days") => 24 * 60 * 60,
+                    Some("weeks") => 7 * 24 * 60 * 60,
+                    _ => 1,
+                };
+                let seconds = |key: &str| {
+                    text(key)
+                        .and_then(|s| s.parse::<i64>().ok())
+                        .map(|n| -n * unit)
+                        .ok_or_else(|| anyhow!("no {} in the link.", key))
+                };
+                LinkRange::Relative {
+                    start: seconds("start")?,
+                    end: seconds("end").unwrap_or(0),
+                }
+            }
+            _ => {
+                let timestamp = |key: &str| {
+                    text(key)
+                        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                        .map(|t| t.timestamp())
+                        .ok_or_else(|| anyhow!("no {} in the link.", key))
+                };
+                LinkRange::Absolute {
+                    start: timestamp("start")?,
+                    end: timestamp("end")?,
+                }
+            }
+        };
+        Ok(ConsoleLink {
+            region: region_of(base),
+            query,
+            groups,
+            range,
+        })
+    }
+}
+
+/// `region=` of the query, or the region of the host like `us-east-1.console.aws.amazon.com`.
+fn region_of(base: &str) -> Option<String> {
+    let (host, query) = match base.split_once('?') {
+        Some((host, query)) => (host, query),
+        None => (base, ""),
+    };
+    query
+        .split('&')
+        .find_map(|p| p.strip_prefix("region="))
+        .map(|r| r.to_owned())
+        .or_else(|| {
+            let host = host.split("://").nth(1)?.split('/').next()?;
+            host.strip_suffix(".console.aws.amazon.com")
+                .filter(|r| !r.is_empty() && !r.contains('.'))
+                .map(|r| r.to_owned())
+        })
+}
+
+/// Escape `s` for a string of the query detail like JSURL does: characters
+/// other than alphanumerics, `-`, `_` and `.` become `*` and two hex digits,
+/// or `**` and four hex digits of each UTF-16 unit above `ff`.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for u in s.encode_utf16() {
+        match u {
+            0..=0x7f if (u as u8).is_ascii_alphanumeric() || b"-_.".contains(&(u as u8)) => {
+                escaped.push(u as u8 as char)
+            }
+            0..=0xff => escaped.push_str(&format!("*{:02x}", u)),
+            _ => escaped.push_str(&format!("**{:04x}", u)),
+        }
+    }
+    escaped
+}
+
+/// Decode the escapes of `escape`, keeping anything else.
+fn unescape_string(s: &str) -> String {
+    let hex = |h: Option<&str>| h.and_then(|h| u16::from_str_radix(h, 16).ok());
+    let mut units = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        if let Some(u) = hex(rest.strip_prefix("**").and_then(|r| r.get(..4))) {
+            units.push(u);
+            i += 6;
+        } else if let Some(u) = hex(rest.strip_prefix('*').and_then(|r| r.get(..2))) {
+            units.push(u);
+            i += 3;
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            let mut buf = [0; 2];
+            units.extend_from_slice(c.encode_utf16(&mut buf));
+            i += c.len_utf8();
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Decode `marker` followed by two hex digits into the byte, keeping anything
+/// else, like percent-decoding with another marker.
+fn unescape(s: &str, marker: char) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(b) if bytes[i] == marker as u8 => {
+                decoded.push(b);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Value of the query detail, where `(k~v~k~v)` is an object, `(~v~v)` a
+/// list, and `'s` or a bare word a string.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    List(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Parser { s, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.peek() != Some(c) {
+            return Err(anyhow!(
+                "expected {:?} at {} of the query detail.",
+                c,
+                self.pos
+            ));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Up to the next `~`, `(` or `)`.
+    fn token(&mut self) -> &'a str {
+        let rest = &self.s[self.pos..];
+        let len = rest
+            .find(|c| c == '~' || c == '(' || c == ')')
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let value = if self.peek() == Some('~') {
+                    let mut items = vec![];
+                    while self.peek() == Some('~') {
+                        self.pos += 1;
+                        items.push(self.value()?);
+                    }
+                    Value::List(items)
+                } else {
+                    let mut fields = vec![];
+                    while !matches!(self.peek(), Some(')') | None) {
+                        let key = self.token().to_owned();
+                        self.expect('~')?;
+                        fields.push((key, self.value()?));
+                        if self.peek() == Some('~') {
+                            self.pos += 1;
+                        }
+                    }
+                    Value::Object(fields)
+                };
+                self.expect(')')?;
+                Ok(value)
+            }
+            Some('\'') => {
+                self.pos += 1;
+                Ok(Value::Str(unescape_string(self.token())))
+            }
+            _ => Ok(Value::Str(self.token().to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let link = ConsoleLink {
+            region: Some("ap-northeast-1".to_owned()),
+            query: "fields @message | filter @message like /~(x) 'y' ログ 😀/".to_owned(),
+            groups: vec!["/aws/lambda/foo".to_owned(), "bar".to_owned()],
+            range: LinkRange::Absolute {
+                start: 1626868800,
+                end: 1626872400,
+            },
+        };
+        assert_eq!(ConsoleLink::parse(&link.url()).unwrap(), link);
+
+        let link = ConsoleLink {
+            region: None,
+            range: LinkRange::Relative {
+                start: 3600,
+                end: 0,
+            },
+            ..link
+        };
+        assert_eq!(ConsoleLink::parse(&link.url()).unwrap(), link);
+    }
+
+    #[test]
+    fn escape_non_ascii() {
+        assert_eq!(escape("a b/é"), "a*20b*2f*e9");
+        assert_eq!(escape("ログ"), "**30ed**30b0");
+        assert_eq!(escape("😀"), "**d83d**de00");
+        assert_eq!(unescape_string("**30ed**30b0*20*e9"), "ログ é");
+    }
+
+    #[test]
+    fn parse_console_link() {
+        let url = "https://console.aws.amazon.com/cloudwatch/home?region=us-east-1#logsV2:logs-insights$3FqueryDetail$3D~(end~0~start~-1~timeType~'RELATIVE~unit~'hours~editorString~'fields*20*40timestamp*2c*20*40message*0a*7c*20sort*20*40timestamp*20desc~isLiveTail~false~queryId~'abc-123~source~(~'*2faws*2flambda*2ffoo~'bar))";
+        assert_eq!(
+            ConsoleLink::parse(url).unwrap(),
+            ConsoleLink {
+                region: Some("us-east-1".to_owned()),
+                query: "fields @timestamp, @message\n| sort @timestamp desc".to_owned(),
+                groups: vec!["/aws/lambda/foo".to_owned(), "bar".to_owned()],
+                range: LinkRange::Relative {
+                    start: 3600,
+                    end: 0
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_address_bar_link() {
+        let url = "https://ap-northeast-1.console.aws.amazon.com/cloudwatch/home?region=ap-northeast-1#logsV2:logs-insights$3FqueryDetail$3D$257E$2528end$257E$25272021-07-21T13*3a00*3a00.000Z$257Estart$257E$25272021-07-21T12*3a00*3a00.000Z$257EtimeType$257E$2527ABSOLUTE$257Etz$257E$2527Local$257EeditorString$257E$2527fields*20*40message*0a*7c*20filter*20*40message*20like*20*2f**30ed**30b0*2f$257EisLiveTail$257Efalse$257Esource$257E$2528$257E$2527*2faws*2flambda*2ffoo$2529$2529";
+        assert_eq!(
+            ConsoleLink::parse(url).unwrap(),
+            ConsoleLink {
+                region: Some("ap-northeast-1".to_owned()),
+                query: "fields @message\n| filter @message like /ログ/".to_owned(),
+                groups: vec!["/aws/lambda/foo".to_owned()],
+                range: LinkRange::Absolute {
+                    start: 1626868800,
+                    end: 1626872400
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_not_a_link() {
+        assert!(ConsoleLink::parse("https://console.aws.amazon.com/cloudwatch/home").is_err());
+        assert!(ConsoleLink::parse("https://example.com/#logsV2:logs-insights").is_err());
+    }
+}
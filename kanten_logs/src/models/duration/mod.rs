@@ -1,7 +1,9 @@
-use chrono;
-use humantime::{parse_duration, parse_rfc3339};
+use chrono::{self, DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use humantime::parse_duration;
 
-#[derive(Debug)]
+use super::Zone;
+
+#[derive(Debug, Clone)]
 pub enum Duration {
     // Live,
     Duration {
@@ -10,7 +12,74 @@ pub enum Duration {
     },
 }
 
-fn parse(s: &str) -> Option<i64> {
+/// Date and time with an offset, like `2021-07-21T12:00:00+09:00` or `2021-07-21T03:00Z`.
+fn parse_datetime_with_offset(s: &str) -> Option<i64> {
+    let s = match s.strip_suffix('Z').or_else(|| s.strip_suffix('z')) {
+        Some(s) => format!("{}+00:00", s),
+        None => s.to_owned(),
+    };
+    [
+        "%Y-%m-%dT%H:%M:%S%.f%:z",
+        "%Y-%m-%dT%H:%M:%S%:z",
+        "%Y-%m-%dT%H:%M%:z",
+        "%Y-%m-%d %H:%M:%S%.f%:z",
+        "%Y-%m-%d %H:%M:%S%:z",
+        "%Y-%m-%d %H:%M%:z",
+    ]
+    .iter()
+    .find_map(|f| DateTime::parse_from_str(&s, f).ok())
+    .map(|t| t.timestamp())
+}
+
+/// Date and time without an offset, like `2021-07-21 12:00`, in `zone`.
+fn parse_datetime(s: &str, zone: &Zone) -> Option<i64> {
+    let time = [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    })?;
+    zone.to_utc(&time).map(|t| t.timestamp())
+}
+
+/// `today`, `yesterday` or `last monday` in `zone`, optionally followed by a time like `14:00`.
+fn parse_day(s: &str, zone: &Zone) -> Option<i64> {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let today = zone.today();
+    let (date, rest) = match words.as_slice() {
+        ["today", rest @ ..] => (today, rest),
+        ["yesterday", rest @ ..] => (today.pred_opt()?, rest),
+        ["last", weekday, rest @ ..] => {
+            let weekday: Weekday = weekday.parse().ok()?;
+            let days =
+                (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+            let days = if days == 0 { 7 } else { days };
+            (today - chrono::Duration::days(days as i64), rest)
+        }
+        _ => return None,
+    };
+    let time = match rest {
+        [] => NaiveTime::from_hms_opt(0, 0, 0)?,
+        [time] => ["%H:%M:%S", "%H:%M"]
+            .iter()
+            .find_map(|f| NaiveTime::parse_from_str(time, f).ok())?,
+        _ => return None,
+    };
+    zone.to_utc(&date.and_time(time)).map(|t| t.timestamp())
+}
+
+/// Parse a point in time, which is `now`, a duration ago like `15m`, or an
+/// absolute time, to a unix timestamp.
+fn parse(s: &str, zone: &Zone) -> Option<i64> {
     let now = chrono::Local::now();
     if s == "now" {
         return Some(now.timestamp());
@@ -19,41 +88,253 @@ fn parse(s: &str) -> Option<i64> {
     if let Ok(d) = parse_duration(s) {
         Some((now - chrono::Duration::seconds(d.as_secs() as i64)).timestamp())
     } else {
-        if let Ok(d) = parse_rfc3339(s) {
-            if let Ok(d) = d.elapsed() {
-                return Some(d.as_secs() as i64);
-            }
-        }
-        None
+        parse_datetime_with_offset(s)
+            .or_else(|| parse_datetime(s, zone))
+            .or_else(|| parse_day(&s.to_lowercase(), zone))
     }
 }
 
 impl Duration {
-    pub fn from_opt(s: &str, e: Option<&str>) -> Self {
+    pub fn from_opt(s: &str, e: Option<&str>, zone: &Zone) -> Self {
         let end = if let Some(e) = e {
-            parse(e)
+            parse(e, zone)
         } else {
-            parse("now")
+            parse("now", zone)
         };
         Self::Duration {
-            start: parse(s),
+            start: parse(s, zone),
             end,
         }
     }
 
+    /// Parse the duration input, like `15m`, `3h - 1h`, `2021-07-21 12:00 - now`
+    /// or `yesterday 14:00 + 30m`. Times without an offset are in `zone`, and
+    /// the end defaults to now.
+    pub fn parse(s: &str, zone: &Zone) -> Self {
+        let s = s.trim();
+        if let Some((start, length)) = s.split_once(" + ") {
+            let start = parse(start.trim(), zone);
+            let length = parse_duration(length.trim()).ok();
+            let end = start.zip(length).map(|(s, l)| s + l.as_secs() as i64);
+            return Duration::Duration { start, end };
+        }
+        let (start, end) = match s.split_once(" - ") {
+            Some((start, end)) => (start, Some(end)),
+            // A date alone has dashes too.
+            None if parse(s, zone).is_some() => (s, None),
+            None => match s.split_once('-') {
+                Some((start, end)) => (start, Some(end)),
+                None => (s, None),
+            },
+        };
+        Self::from_opt(start.trim(), end.map(str::trim), zone)
+    }
+
     pub fn is_valid(&self) -> bool {
         match self {
             Duration::Duration { start, end } => start.is_some() && end.is_some(),
             // _ => todo!(),
         }
     }
+
+    fn range(&self) -> Option<(i64, i64)> {
+        match self {
+            Duration::Duration { start, end } => start.zip(*end),
+        }
+    }
+
+    /// Move the range by `n` times its width, without going past now.
+    pub fn shift(&self, n: i64) -> Option<Self> {
+        let (start, end) = self.range()?;
+        let width = end - start;
+        let now = chrono::Utc::now().timestamp();
+        let end = (end + width * n).min(now.max(end));
+        Some(Duration::Duration {
+            start: Some(end - width),
+            end: Some(end),
+        })
+    }
+
+    /// Scale the width of the range by `factor` around its centre, keeping it at least a minute.
+    pub fn zoom(&self, factor: f64) -> Option<Self> {
+        let (start, end) = self.range()?;
+        let centre = start + (end - start) / 2;
+        let half = (((end - start) as f64 * factor) as i64 / 2).max(30);
+        Some(Duration::Duration {
+            start: Some(centre - half),
+            end: Some(centre + half),
+        })
+    }
+
+    /// The range of `half` seconds before and after `timestamp`.
+    pub fn around(timestamp: i64, half: i64) -> Self {
+        Duration::Duration {
+            start: Some(timestamp - half),
+            end: Some(timestamp + half),
+        }
+    }
+
+    /// Duration input expression of the range, with the times in `zone`.
+    pub fn expression(&self, zone: &Zone) -> Option<String> {
+        let (start, end) = self.range()?;
+        let format = "%Y-%m-%dT%H:%M:%S";
+        Some(format!(
+            "{} - {}",
+            zone.to_local(start).format(format),
+            zone.to_local(end).format(format)
+        ))
+    }
 }
 
-impl From<&str> for Duration {
-    fn from(s: &str) -> Self {
-        let s: Vec<Option<i64>> = s.split('-').map(|s| parse(s.trim())).collect();
-        let start: Option<i64> = s.get(0).and_then(|i| *i);
-        let end: Option<i64> = s.get(1).and_then(|i| *i);
-        Duration::Duration { start, end }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2021-07-21T00:00:00Z
+    const DAY: i64 = 1626825600;
+
+    fn range(s: &str, zone: &Zone) -> (Option<i64>, Option<i64>) {
+        let Duration::Duration { start, end } = Duration::parse(s, zone);
+        (start, end)
+    }
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    fn assert_near(t: Option<i64>, expected: i64) {
+        let t = t.expect("not parsed");
+        assert!((t - expected).abs() <= 2, "{} is not near {}", t, expected);
+    }
+
+    fn midnight(date: NaiveDate, zone: &Zone) -> i64 {
+        zone.to_utc(&date.and_hms_opt(0, 0, 0).unwrap())
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn relative() {
+        let (start, end) = range("15m", &Zone::Utc);
+        assert_near(start, now() - 15 * 60);
+        assert_near(end, now());
+
+        let (start, end) = range("3h - 1h", &Zone::Utc);
+        assert_near(start, now() - 3 * 60 * 60);
+        assert_near(end, now() - 60 * 60);
+
+        let (start, end) = range("1h - now", &Zone::Utc);
+        assert_near(start, now() - 60 * 60);
+        assert_near(end, now());
+    }
+
+    #[test]
+    fn absolute_with_offset() {
+        let tokyo = DAY + 3 * 60 * 60;
+        assert_eq!(
+            range(
+                "2021-07-21T12:00:00+09:00 - 2021-07-21T13:00:00+09:00",
+                &Zone::Utc
+            ),
+            (Some(tokyo), Some(tokyo + 60 * 60))
+        );
+        assert_eq!(
+            range("2021-07-21T03:00Z - 2021-07-21T04:00:00.500z", &Zone::Utc),
+            (Some(tokyo), Some(tokyo + 60 * 60))
+        );
+        assert_eq!(
+            range("2021-07-21 12:00+09:00 + 30m", &Zone::Utc),
+            (Some(tokyo), Some(tokyo + 30 * 60))
+        );
+    }
+
+    #[test]
+    fn absolute_in_zone() {
+        let noon = DAY + 12 * 60 * 60;
+        assert_eq!(
+            range("2021-07-21 12:00 - 2021-07-21T13:00:30", &Zone::Utc),
+            (Some(noon), Some(noon + 60 * 60 + 30))
+        );
+        let tokyo = Zone::Named("Asia/Tokyo".parse().unwrap());
+        assert_eq!(
+            range("2021-07-21 12:00 - 2021-07-21 13:00", &tokyo),
+            (Some(noon - 9 * 60 * 60), Some(noon - 8 * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn length() {
+        let noon = DAY + 12 * 60 * 60;
+        assert_eq!(
+            range("2021-07-21 12:00 + 1h 30m", &Zone::Utc),
+            (Some(noon), Some(noon + 90 * 60))
+        );
+        let (start, end) = range("yesterday 14:00 + 30m", &Zone::Utc);
+        let yesterday = midnight(Zone::Utc.today().pred_opt().unwrap(), &Zone::Utc);
+        assert_eq!(start, Some(yesterday + 14 * 60 * 60));
+        assert_eq!(end, Some(yesterday + 14 * 60 * 60 + 30 * 60));
+
+        // A length which is not a duration leaves the end out.
+        let (start, end) = range("2021-07-21 12:00 + soon", &Zone::Utc);
+        assert_eq!((start, end), (Some(noon), None));
+    }
+
+    #[test]
+    fn days() {
+        let zone = Zone::Utc;
+        let today = zone.today();
+        let (start, end) = range("today", &zone);
+        assert_eq!(start, Some(midnight(today, &zone)));
+        assert_near(end, now());
+
+        let (start, _) = range("Yesterday 09:30", &zone);
+        assert_eq!(
+            start,
+            Some(midnight(today.pred_opt().unwrap(), &zone) + 9 * 60 * 60 + 30 * 60)
+        );
+
+        let (start, end) = range("last monday - today", &zone);
+        let start = start.unwrap();
+        let days = (midnight(today, &zone) - start) / (24 * 60 * 60);
+        assert!((1..=7).contains(&days));
+        assert_eq!((midnight(today, &zone) - start) % (24 * 60 * 60), 0);
+        assert_eq!(zone.to_local(start).date().weekday(), chrono::Weekday::Mon);
+        assert_eq!(end, Some(midnight(today, &zone)));
+
+        assert!(!Duration::parse("last someday", &zone).is_valid());
+    }
+
+    #[test]
+    fn dashes() {
+        // A date alone is not split at its dashes.
+        let (start, end) = range("2021-07-21", &Zone::Utc);
+        assert_eq!(start, Some(DAY));
+        assert_near(end, now());
+
+        // Dates and times are split only at ` - `.
+        assert_eq!(
+            range("2021-07-20 - 2021-07-21", &Zone::Utc),
+            (Some(DAY - 24 * 60 * 60), Some(DAY))
+        );
+        assert_eq!(
+            range("2021-07-21T03:00Z - 2021-07-21T04:00Z", &Zone::Utc),
+            (Some(DAY + 3 * 60 * 60), Some(DAY + 4 * 60 * 60))
+        );
+
+        // Durations may be split at a bare `-`.
+        let (start, end) = range("3h-1h", &Zone::Utc);
+        assert_near(start, now() - 3 * 60 * 60);
+        assert_near(end, now() - 60 * 60);
+
+        // Dates joined by a bare `-` are split at the first dash, which is not a range.
+        assert!(!Duration::parse("2021-07-20-2021-07-21", &Zone::Utc).is_valid());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(!Duration::parse("", &Zone::Utc).is_valid());
+        assert!(!Duration::parse("soon", &Zone::Utc).is_valid());
+        assert!(!Duration::parse("15m - soon", &Zone::Utc).is_valid());
+        assert!(!Duration::parse("2021-13-01", &Zone::Utc).is_valid());
     }
 }
@@ -0,0 +1,30 @@
+/// Score how well `pattern` matches `text` as a case insensitive subsequence.
+/// Returns `None` if some char of `pattern` is missing. Higher is better.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<i64> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+    for p in pattern
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+    {
+        let i = pos + text[pos..].iter().position(|c| *c == p)?;
+        score += 1;
+        // Prefer runs of consecutive chars and chars at the start of a word.
+        if prev.map(|prev| prev + 1 == i).unwrap_or(false) {
+            score += 5;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (i - pos) as i64;
+        prev = Some(i);
+        pos = i + 1;
+    }
+    Some(score)
+}
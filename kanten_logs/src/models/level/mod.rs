@@ -0,0 +1,238 @@
+use std::fmt;
+
+/// Severity of a log message, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    /// Parse a level name like `warn`, `WARNING` or `E`, ignoring case.
+    pub fn from_name(s: &str) -> Option<Level> {
+        let level = match s.to_ascii_lowercase().as_str() {
+            "trace" | "t" => Level::Trace,
+            "debug" | "d" => Level::Debug,
+            "info" | "information" | "notice" | "i" => Level::Info,
+            "warn" | "warning" | "w" => Level::Warn,
+            "error" | "err" | "fatal" | "critical" | "crit" | "alert" | "emergency" | "e" => {
+                Level::Error
+            }
+            _ => return None,
+        };
+        Some(level)
+    }
+
+    /// Level of a numeric level as used by pino and bunyan.
+    fn from_number(n: f64) -> Level {
+        match n as i64 {
+            i64::MIN..=10 => Level::Trace,
+            11..=20 => Level::Debug,
+            21..=30 => Level::Info,
+            31..=40 => Level::Warn,
+            _ => Level::Error,
+        }
+    }
+
+    /// Guess the level of a log message.
+    ///
+    /// It looks at, in order, the lines Lambda writes around each invocation,
+    /// the `level` field of a JSON message, a `level=` pair of logfmt or a
+    /// `"level":` field in the message, a bracketed level like `[WARN]`, and an
+    /// upper case level name like `ERROR` leading the message, after any
+    /// timestamps, ids and bracketed fields.
+    pub fn detect(message: &str) -> Option<Level> {
+        let message = message.trim_start();
+        if let Some(level) = detect_lambda(message) {
+            return Some(level);
+        }
+        if message.starts_with('{') {
+            if let Some(level) = detect_json(message) {
+                return Some(level);
+            }
+        }
+        detect_field(message)
+            .or_else(|| detect_bracketed(message))
+            .or_else(|| detect_leading(message))
+    }
+}
+
+/// Tokens looked at for a level leading the message.
+const LEADING_TOKENS: usize = 5;
+
+/// An upper case level name like `ERROR`, `[WARN]` or `INFO:`.
+fn level_name(token: &str) -> Option<Level> {
+    let name = token
+        .trim_start_matches('[')
+        .trim_end_matches(|c| c == ']' || c == ':');
+    if name.len() > 1 && name.chars().all(|c| c.is_ascii_uppercase()) {
+        Level::from_name(name)
+    } else {
+        None
+    }
+}
+
+/// Value of a `level=` pair or of a `"level":` field.
+fn detect_field(message: &str) -> Option<Level> {
+    message.match_indices("level").find_map(|(i, key)| {
+        let before = message[..i].chars().next_back();
+        if before.map_or(false, |c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+        let rest = &message[i + key.len()..];
+        let value = if before == Some('"') {
+            rest.strip_prefix('"')?.trim_start().strip_prefix(':')?
+        } else {
+            rest.strip_prefix('=')?
+        };
+        let value = value
+            .trim_start()
+            .trim_start_matches('"')
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default();
+        Level::from_name(value)
+    })
+}
+
+/// An upper case level in brackets anywhere, like `[ERROR]`.
+fn detect_bracketed(message: &str) -> Option<Level> {
+    message.match_indices('[').find_map(|(i, _)| {
+        let rest = &message[i + 1..];
+        let name = &rest[..rest.find(']')?];
+        if name.len() > 1 && name.chars().all(|c| c.is_ascii_uppercase()) {
+            Level::from_name(name)
+        } else {
+            None
+        }
+    })
+}
+
+/// A level name leading the message, after tokens with digits, like
+/// timestamps and ids, and bracketed ones, like `[main]`.
+fn detect_leading(message: &str) -> Option<Level> {
+    for token in message.split_whitespace().take(LEADING_TOKENS) {
+        if let Some(level) = level_name(token) {
+            return Some(level);
+        }
+        if !token.starts_with('[') && !token.chars().any(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+    None
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// `START`, `END` and `REPORT` lines are Lambda bookkeeping, so they are
+/// debug, unless the report says the invocation failed.
+fn detect_lambda(message: &str) -> Option<Level> {
+    if message.starts_with("START RequestId:") || message.starts_with("END RequestId:") {
+        return Some(Level::Debug);
+    }
+    if message.starts_with("REPORT RequestId:") {
+        return Some(if message.contains("Status: ") {
+            Level::Error
+        } else {
+            Level::Debug
+        });
+    }
+    None
+}
+
+fn detect_json(message: &str) -> Option<Level> {
+    let value: serde_json::Value = serde_json::from_str(message).ok()?;
+    let object = value.as_object()?;
+    ["level", "severity", "levelname", "log.level", "lvl"]
+        .iter()
+        .find_map(|key| object.get(*key))
+        .and_then(|level| match level {
+            serde_json::Value::String(s) => Level::from_name(s),
+            serde_json::Value::Number(n) => n.as_f64().map(Level::from_number),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading() {
+        assert_eq!(Level::detect("ERROR failed"), Some(Level::Error));
+        assert_eq!(Level::detect("INFO: started"), Some(Level::Info));
+        assert_eq!(
+            Level::detect("2021-07-21T12:00:00.000Z\t1b2c-3d4e\tWARN\tslow"),
+            Some(Level::Warn)
+        );
+        assert_eq!(
+            Level::detect("2021-07-21 12:00:00,123 [main] DEBUG com.example.App - ready"),
+            Some(Level::Debug)
+        );
+    }
+
+    #[test]
+    fn bracketed() {
+        assert_eq!(
+            Level::detect("[ERROR]\t2021-07-21T12:00:00Z\tboom"),
+            Some(Level::Error)
+        );
+        assert_eq!(Level::detect("handler [WARN] retrying"), Some(Level::Warn));
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(Level::detect("ts=1 level=warn msg=x"), Some(Level::Warn));
+        assert_eq!(
+            Level::detect(r#"level="error" msg="x""#),
+            Some(Level::Error)
+        );
+        assert_eq!(
+            Level::detect(r#"handler {"level": "debug", "msg": "x"}"#),
+            Some(Level::Debug)
+        );
+        assert_eq!(
+            Level::detect(r#"{"severity":"ERROR","msg":"x"}"#),
+            Some(Level::Error)
+        );
+        assert_eq!(Level::detect(r#"{"level":50}"#), Some(Level::Error));
+    }
+
+    #[test]
+    fn lambda() {
+        assert_eq!(
+            Level::detect("START RequestId: 1 Version: $LATEST"),
+            Some(Level::Debug)
+        );
+        assert_eq!(
+            Level::detect("REPORT RequestId: 1 Duration: 3 ms Status: timeout"),
+            Some(Level::Error)
+        );
+    }
+
+    #[test]
+    fn not_a_level() {
+        assert_eq!(Level::detect("no ERROR found"), None);
+        assert_eq!(Level::detect("retried 3 times, last status ERROR"), None);
+        assert_eq!(Level::detect("[main] processing ERROR codes"), None);
+        assert_eq!(Level::detect("status=ERROR code=500"), None);
+        assert_eq!(Level::detect("loglevel=error"), None);
+        assert_eq!(Level::detect(r#"{"msg":"WARN users"}"#), None);
+        assert_eq!(Level::detect("the [Error] was handled"), None);
+        assert_eq!(Level::detect("error handling done"), None);
+    }
+}
@@ -1,3 +1,12 @@
+pub mod console;
 pub mod duration;
+pub mod fuzzy;
+pub mod level;
+pub mod template;
+pub mod timestamp;
 
+pub use console::*;
 pub use duration::*;
+pub use fuzzy::*;
+pub use level::*;
+pub use timestamp::*;
@@ -0,0 +1,146 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Replace the parts of `message` which usually differ between messages of the
+/// same kind, so that they compare equal. UUIDs become `<uuid>`, hex strings
+/// `<hex>` and numbers `<num>`.
+pub fn mask(message: &str) -> String {
+    let mut masked = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find(is_word_char) {
+        masked.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c| !is_word_char(c))
+            .unwrap_or_else(|| rest.len());
+        mask_word(&rest[..end], &mut masked);
+        rest = &rest[end..];
+    }
+    masked.push_str(rest);
+    masked
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-'
+}
+
+fn mask_word(word: &str, masked: &mut String) {
+    if is_uuid(word) {
+        masked.push_str("<uuid>");
+        return;
+    }
+    for (i, part) in word.split('-').enumerate() {
+        if i > 0 {
+            masked.push('-');
+        }
+        masked.push_str(mask_part(part));
+    }
+}
+
+fn mask_part(part: &str) -> &str {
+    let has_digit = part.chars().any(|c| c.is_ascii_digit());
+    if !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()) {
+        "<num>"
+    } else if part.len() > 2 && (part.starts_with("0x") || part.starts_with("0X")) {
+        "<hex>"
+    } else if part.chars().all(|c| c.is_ascii_hexdigit()) && (has_digit || part.len() >= 16) {
+        "<hex>"
+    } else if has_digit && part.starts_with(|c: char| c.is_ascii_digit()) {
+        // Numbers with a unit like `12ms` or `3GB`.
+        "<num>"
+    } else {
+        part
+    }
+}
+
+/// `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` of hex digits.
+fn is_uuid(word: &str) -> bool {
+    let lens: Vec<usize> = word.split('-').map(str::len).collect();
+    lens == [8, 4, 4, 4, 12] && word.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+}
+
+/// `message` with its variable tokens masked and whitespace normalized.
+/// Messages with the same template are of the same kind.
+pub fn template_of(message: &str) -> String {
+    message
+        .split_whitespace()
+        .map(mask)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Messages sharing a template.
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    pub template: String,
+    pub count: usize,
+    /// First message of the cluster.
+    pub sample: String,
+}
+
+/// Group `messages` by template, most frequent first.
+pub fn cluster<'a>(messages: impl IntoIterator<Item = &'a str>) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = vec![];
+    let mut index_of: HashMap<String, usize> = HashMap::new();
+    for message in messages {
+        let template = template_of(message);
+        match index_of.get(&template) {
+            Some(i) => clusters[*i].count += 1,
+            None => {
+                index_of.insert(template.clone(), clusters.len());
+                clusters.push(Cluster {
+                    template,
+                    count: 1,
+                    sample: message.to_owned(),
+                });
+            }
+        }
+    }
+    // Stable sort keeps the first seen first among equally frequent ones.
+    clusters.sort_by(|a, b| b.count.cmp(&a.count));
+    clusters
+}
+
+/// Which of two result sets the template of a message appears in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    OnlyA,
+    OnlyB,
+    Both,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Side::OnlyA => "only A",
+            Side::OnlyB => "only B",
+            Side::Both => "both",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Sides of the messages of `a` and of `b`, matching messages by template.
+pub fn diff<'a>(
+    a: impl IntoIterator<Item = &'a str>,
+    b: impl IntoIterator<Item = &'a str>,
+) -> (Vec<Side>, Vec<Side>) {
+    let a: Vec<String> = a.into_iter().map(template_of).collect();
+    let b: Vec<String> = b.into_iter().map(template_of).collect();
+    let in_a: HashSet<&String> = a.iter().collect();
+    let in_b: HashSet<&String> = b.iter().collect();
+    let side = |shared: bool, only: Side| if shared { Side::Both } else { only };
+    (
+        a.iter()
+            .map(|t| side(in_b.contains(t), Side::OnlyA))
+            .collect(),
+        b.iter()
+            .map(|t| side(in_a.contains(t), Side::OnlyB))
+            .collect(),
+    )
+}
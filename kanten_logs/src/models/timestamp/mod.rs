@@ -0,0 +1,172 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Parse `@timestamp` of Insights, like `2021-07-21 12:34:56.789`, which is in UTC.
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|t| Utc.from_utc_datetime(&t))
+}
+
+/// Time zone times are shown and typed in.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Zone {
+    Utc,
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    pub fn format(&self, time: &DateTime<Utc>, format: &str) -> String {
+        match self {
+            Zone::Utc => time.format(format).to_string(),
+            Zone::Local => time.with_timezone(&Local).format(format).to_string(),
+            Zone::Named(tz) => time.with_timezone(tz).format(format).to_string(),
+        }
+    }
+
+    /// `time` as a time in this zone. The earlier one is used for ambiguous times.
+    pub fn to_utc(&self, time: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Utc => Some(Utc.from_utc_datetime(time)),
+            Zone::Local => Local
+                .from_local_datetime(time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            Zone::Named(tz) => tz
+                .from_local_datetime(time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+
+    /// Unix `timestamp` as a time in this zone.
+    pub fn to_local(&self, timestamp: i64) -> NaiveDateTime {
+        let time = Utc
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .unwrap_or_else(Utc::now);
+        match self {
+            Zone::Utc => time.naive_utc(),
+            Zone::Local => time.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => time.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// Today in this zone.
+    pub fn today(&self) -> NaiveDate {
+        let now = Utc::now();
+        match self {
+            Zone::Utc => now.naive_utc().date(),
+            Zone::Local => now.with_timezone(&Local).naive_local().date(),
+            Zone::Named(tz) => now.with_timezone(tz).naive_local().date(),
+        }
+    }
+}
+
+impl Default for Zone {
+    fn default() -> Self {
+        Zone::Utc
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Zone::Utc => write!(f, "UTC"),
+            Zone::Local => write!(f, "local time"),
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl FromStr for Zone {
+    type Err = String;
+
+    /// `utc`, `local` or an IANA time zone name like `Asia/Tokyo`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utc" => Ok(Zone::Utc),
+            "local" => Ok(Zone::Local),
+            _ => s
+                .parse::<Tz>()
+                .map(Zone::Named)
+                .map_err(|_| format!("unknown time zone {:?}", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Zone {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// How timestamps of rows are shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimestampStyle {
+    pub zone: Zone,
+    /// Show how long ago instead, like `3m ago`.
+    pub relative: bool,
+    /// `strftime` like format of absolute times.
+    pub format: String,
+}
+
+impl TimestampStyle {
+    pub fn new(zone: Zone, format: impl Into<String>) -> Self {
+        TimestampStyle {
+            zone,
+            relative: false,
+            format: format.into(),
+        }
+    }
+
+    pub fn format(&self, time: &DateTime<Utc>) -> String {
+        if self.relative {
+            relative(*time - Utc::now())
+        } else {
+            self.zone.format(time, &self.format)
+        }
+    }
+
+    /// The style after this one, cycling through UTC, local time,
+    /// `configured` if it is a named zone, and relative times.
+    pub fn next(&self, configured: Zone) -> TimestampStyle {
+        let mut next = self.clone();
+        if self.relative {
+            next.relative = false;
+            next.zone = Zone::Utc;
+            return next;
+        }
+        match (self.zone, configured) {
+            (Zone::Utc, _) => next.zone = Zone::Local,
+            (Zone::Local, Zone::Named(_)) => next.zone = configured,
+            _ => next.relative = true,
+        }
+        next
+    }
+}
+
+/// Like `3m ago` or `in 5s`, in the largest unit.
+fn relative(d: chrono::Duration) -> String {
+    let secs = d.num_seconds().abs();
+    let amount = match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    };
+    if d.num_seconds() > 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
@@ -1,11 +1,19 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
+use crate::{
+    client::filter_of_query,
+    config::{Config, DEFAULT_SINCE},
+    models::ConsoleLink,
+};
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "kanten")]
 pub struct Opt {
     /// Return logs newer than a relative duration like 52, 2m, or 3h. (default: "15m")
-    #[structopt(short, long, default_value = "15m")]
-    pub since: String,
+    #[structopt(short, long)]
+    pub since: Option<String>,
 
     /// Return logs older than a relative duration like 0, 2m, or 3h.
     #[structopt(short, long)]
@@ -17,4 +25,62 @@ pub struct Opt {
 
     #[structopt(short, long, default_value = "")]
     pub filter: String,
+
+    /// AWS profile name.
+    #[structopt(long)]
+    pub profile: Option<String>,
+
+    /// AWS region.
+    #[structopt(long)]
+    pub region: Option<String>,
+
+    /// Path to config file. (default: "$XDG_CONFIG_HOME/kanten/config.toml")
+    #[structopt(short, long, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    /// Restore the session saved on the last exit, or the one saved in the given file.
+    #[structopt(long)]
+    pub resume: Option<Option<PathBuf>>,
+
+    /// Prefill the query from a link to Logs Insights in the AWS console.
+    #[structopt(long)]
+    pub from_url: Option<String>,
+}
+
+impl Opt {
+    /// Fill options not given on the command line from `config`.
+    pub fn merge(mut self, config: &Config) -> Self {
+        self.since = self
+            .since
+            .or_else(|| config.since.clone())
+            .or_else(|| Some(DEFAULT_SINCE.to_owned()));
+        self.group_name = self.group_name.or_else(|| config.group_name.clone());
+        self.profile = self.profile.or_else(|| config.profile.clone());
+        self.region = self.region.or_else(|| config.region.clone());
+        self
+    }
+
+    /// Fill options not given on the command line from the query of `link`.
+    pub fn prefill(mut self, link: &ConsoleLink) -> Self {
+        if self.since.is_none() && self.end.is_none() {
+            self.since = Some(link.range.since());
+            self.end = Some(link.range.end());
+        }
+        if self.group_name.is_none() && !link.groups.is_empty() {
+            let names: Vec<String> = link.groups.iter().map(|g| regex::escape(g)).collect();
+            self.group_name = Some(format!("^({})$", names.join("|")));
+        }
+        if self.filter.is_empty() {
+            match filter_of_query(&link.query) {
+                Some(filter) => self.filter = filter,
+                None => log::warn!("query of the link is not a filter {:?}", link.query),
+            }
+        }
+        self.region = self.region.or_else(|| link.region.clone());
+        self
+    }
+
+    pub fn since(&self) -> &str {
+        self.since.as_deref().unwrap_or(DEFAULT_SINCE)
+    }
 }
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::template::Side;
+
+/// Tabs of a run of kanten with their loaded results, so that they can be
+/// opened again without querying, or handed to someone else as a file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<TabSession>,
+    /// Position of the shown tab.
+    #[serde(default)]
+    pub tab_index: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TabSession {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub filter: String,
+    /// Duration as typed, like `15m` or `yesterday 14:00 + 30m`.
+    #[serde(default)]
+    pub duration: String,
+    /// Time range the results were queried for, which a relative duration
+    /// does not keep.
+    #[serde(default)]
+    pub start: Option<i64>,
+    #[serde(default)]
+    pub end: Option<i64>,
+    #[serde(default)]
+    pub group_filter: String,
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub find: String,
+    /// Selected row of the logs pane.
+    #[serde(default)]
+    pub selected: Option<usize>,
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default)]
+    pub rows: Vec<Row>,
+}
+
+/// One result row of a query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Row {
+    pub timestamp: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<Side>,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self> {
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read session file {:?}.", path))?;
+        serde_json::from_str(&s)
+            .with_context(|| format!("failed to parse session file {:?}.", path))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let s = serde_json::to_string(self)?;
+        std::fs::write(path, s).with_context(|| format!("failed to write session file {:?}.", path))
+    }
+
+    /// Whether any tab has rows, which makes the session worth saving.
+    pub fn has_results(&self) -> bool {
+        self.tabs.iter().any(|tab| !tab.rows.is_empty())
+    }
+
+    /// Log groups of all tabs, to list when they can't be fetched.
+    pub fn groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = self
+            .tabs
+            .iter()
+            .flat_map(|tab| tab.groups.iter().cloned())
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+}
+
+/// `session.json` in the cache dir, where the session is saved on exit.
+pub fn default_session_path() -> Result<PathBuf> {
+    let mut path = crate::get_app_cache_path()?;
+    path.push("session.json");
+    Ok(path)
+}